@@ -1,22 +1,31 @@
 use anyhow::{Result, anyhow};
 use twilight_model::{
-    application::command::CommandOptionType,
+    application::{
+        command::{CommandOptionType, CommandOptionValue as InteractionCommandOptionValue},
+        interaction::{InteractionChannel, InteractionDataResolved, InteractionMember},
+    },
+    channel::Attachment,
+    guild::Role,
     id::{
         Id,
         marker::{ChannelMarker, GenericMarker, RoleMarker, UserMarker},
     },
+    user::User,
 };
 
-use crate::arguments::{ArgumentConverter, CommandOption, Error, ToOption};
+use crate::arguments::{
+    ArgumentConverter, CommandOption, Error, MAX_INTEGER_VALUE, MIN_INTEGER_VALUE, ParseContext,
+    RangeError, ToOption,
+};
 
 use twilight_model::application::interaction::application_command::CommandOptionValue;
 
 impl ArgumentConverter for String {
-    fn convert(data: &CommandOptionValue) -> Result<Self> {
+    fn convert(data: &CommandOptionValue, _context: &ParseContext<'_>) -> Result<Self> {
         if let CommandOptionValue::String(value) = data {
             Ok(value.clone())
         } else {
-            Err(anyhow!(Error::InvalidType))
+            Err(anyhow!(Error::wrong_kind(CommandOptionType::String, data)))
         }
     }
 }
@@ -28,56 +37,88 @@ impl ToOption for String {
 }
 
 // --- Numeric Types ---
-macro_rules! numeric_converter {
-    ($ty:ty, $variant:expr) => {
+// Integer options advertise the natural bounds of their type, clamped to the range Discord
+// accepts, so out-of-range input is rejected by the client instead of at parse time.
+macro_rules! integer_converter {
+    ($ty:ty) => {
         impl ArgumentConverter for $ty {
-            fn convert(data: &CommandOptionValue) -> Result<Self> {
-                if let CommandOptionValue::Number(value) = data {
-                    Ok(*value as $ty)
+            fn convert(data: &CommandOptionValue, _context: &ParseContext<'_>) -> Result<Self> {
+                if let CommandOptionValue::Integer(value) = data {
+                    <$ty>::try_from(*value).map_err(|_| {
+                        anyhow!(Error::out_of_range(RangeError::Type {
+                            value: *value,
+                            ty: stringify!($ty),
+                        }))
+                    })
                 } else {
-                    Err(anyhow!(Error::InvalidType))
+                    Err(anyhow!(Error::wrong_kind(CommandOptionType::Integer, data)))
                 }
             }
         }
 
         impl ToOption for $ty {
             fn to_option() -> CommandOption {
-                CommandOption::new($variant)
+                let min = i64::try_from(<$ty>::MIN)
+                    .unwrap_or(MIN_INTEGER_VALUE)
+                    .max(MIN_INTEGER_VALUE);
+                let max = i64::try_from(<$ty>::MAX)
+                    .unwrap_or(MAX_INTEGER_VALUE)
+                    .min(MAX_INTEGER_VALUE);
+                CommandOption::new(CommandOptionType::Integer)
+                    .min_value(InteractionCommandOptionValue::Integer(min))
+                    .max_value(InteractionCommandOptionValue::Integer(max))
             }
         }
     };
+}
+
+macro_rules! float_converter {
     ($ty:ty) => {
-        numeric_converter!($ty, CommandOptionType::Number);
+        impl ArgumentConverter for $ty {
+            fn convert(data: &CommandOptionValue, _context: &ParseContext<'_>) -> Result<Self> {
+                match data {
+                    CommandOptionValue::Number(value) => Ok(*value as $ty),
+                    CommandOptionValue::Integer(value) => Ok(*value as $ty),
+                    _ => Err(anyhow!(Error::wrong_kind(CommandOptionType::Number, data))),
+                }
+            }
+        }
+
+        impl ToOption for $ty {
+            fn to_option() -> CommandOption {
+                CommandOption::new(CommandOptionType::Number)
+            }
+        }
     };
 }
 
 // Signed types
-numeric_converter!(i8, CommandOptionType::Integer);
-numeric_converter!(i16, CommandOptionType::Integer);
-numeric_converter!(i32, CommandOptionType::Integer);
-numeric_converter!(i64, CommandOptionType::Integer);
-numeric_converter!(i128, CommandOptionType::Integer);
-numeric_converter!(isize, CommandOptionType::Integer);
+integer_converter!(i8);
+integer_converter!(i16);
+integer_converter!(i32);
+integer_converter!(i64);
+integer_converter!(i128);
+integer_converter!(isize);
 
 // Unsigned types
-numeric_converter!(u8, CommandOptionType::Integer);
-numeric_converter!(u16, CommandOptionType::Integer);
-numeric_converter!(u32, CommandOptionType::Integer);
-numeric_converter!(u64, CommandOptionType::Integer);
-numeric_converter!(u128, CommandOptionType::Integer);
-numeric_converter!(usize, CommandOptionType::Integer);
+integer_converter!(u8);
+integer_converter!(u16);
+integer_converter!(u32);
+integer_converter!(u64);
+integer_converter!(u128);
+integer_converter!(usize);
 
 // Floating point types
-numeric_converter!(f32);
-numeric_converter!(f64);
+float_converter!(f32);
+float_converter!(f64);
 
 // --- Boolean Type ---
 impl ArgumentConverter for bool {
-    fn convert(data: &CommandOptionValue) -> Result<Self> {
+    fn convert(data: &CommandOptionValue, _context: &ParseContext<'_>) -> Result<Self> {
         if let CommandOptionValue::Boolean(v) = data {
             Ok(*v)
         } else {
-            Err(anyhow!(Error::InvalidType))
+            Err(anyhow!(Error::wrong_kind(CommandOptionType::Boolean, data)))
         }
     }
 }
@@ -90,12 +131,14 @@ impl ToOption for bool {
 
 // --- Char Type ---
 impl ArgumentConverter for char {
-    fn convert(data: &CommandOptionValue) -> Result<Self> {
+    fn convert(data: &CommandOptionValue, _context: &ParseContext<'_>) -> Result<Self> {
         if let CommandOptionValue::String(value) = data {
             let mut chars = value.chars();
-            Ok(chars.next().ok_or_else(|| anyhow!(Error::InvalidType))?)
+            Ok(chars
+                .next()
+                .ok_or_else(|| anyhow!(Error::out_of_range(RangeError::TooShort { min: 1 })))?)
         } else {
-            Err(anyhow!(Error::InvalidType))
+            Err(anyhow!(Error::wrong_kind(CommandOptionType::String, data)))
         }
     }
 }
@@ -107,11 +150,11 @@ impl ToOption for char {
 
 // --- User ID Type ---
 impl ArgumentConverter for Id<UserMarker> {
-    fn convert(data: &CommandOptionValue) -> Result<Self> {
+    fn convert(data: &CommandOptionValue, _context: &ParseContext<'_>) -> Result<Self> {
         if let CommandOptionValue::User(user) = data {
             Ok(*user)
         } else {
-            Err(anyhow!(Error::InvalidType))
+            Err(anyhow!(Error::wrong_kind(CommandOptionType::User, data)))
         }
     }
 }
@@ -124,11 +167,11 @@ impl ToOption for Id<UserMarker> {
 
 // --- Role ID Type ---
 impl ArgumentConverter for Id<RoleMarker> {
-    fn convert(data: &CommandOptionValue) -> Result<Self> {
+    fn convert(data: &CommandOptionValue, _context: &ParseContext<'_>) -> Result<Self> {
         if let CommandOptionValue::Role(role) = data {
             Ok(*role)
         } else {
-            Err(anyhow!(Error::InvalidType))
+            Err(anyhow!(Error::wrong_kind(CommandOptionType::Role, data)))
         }
     }
 }
@@ -140,11 +183,11 @@ impl ToOption for Id<RoleMarker> {
 }
 
 impl ArgumentConverter for Id<ChannelMarker> {
-    fn convert(data: &CommandOptionValue) -> Result<Self> {
+    fn convert(data: &CommandOptionValue, _context: &ParseContext<'_>) -> Result<Self> {
         if let CommandOptionValue::Channel(channel) = data {
             Ok(*channel)
         } else {
-            Err(anyhow!(Error::InvalidType))
+            Err(anyhow!(Error::wrong_kind(CommandOptionType::Channel, data)))
         }
     }
 }
@@ -157,11 +200,14 @@ impl ToOption for Id<ChannelMarker> {
 }
 
 impl ArgumentConverter for Id<GenericMarker> {
-    fn convert(data: &CommandOptionValue) -> Result<Self> {
+    fn convert(data: &CommandOptionValue, _context: &ParseContext<'_>) -> Result<Self> {
         if let CommandOptionValue::Mentionable(channel) = data {
             Ok(*channel)
         } else {
-            Err(anyhow!(Error::InvalidType))
+            Err(anyhow!(Error::wrong_kind(
+                CommandOptionType::Mentionable,
+                data
+            )))
         }
     }
 }
@@ -172,3 +218,189 @@ impl ToOption for Id<GenericMarker> {
         CommandOption::new(CommandOptionType::Mentionable)
     }
 }
+
+// --- Attachment Type ---
+impl ArgumentConverter for Attachment {
+    fn convert(data: &CommandOptionValue, context: &ParseContext<'_>) -> Result<Self> {
+        if let CommandOptionValue::Attachment(id) = data {
+            resolve(context, |resolved| resolved.attachments.get(id))
+        } else {
+            Err(anyhow!(Error::wrong_kind(
+                CommandOptionType::Attachment,
+                data
+            )))
+        }
+    }
+}
+
+impl ToOption for Attachment {
+    fn to_option() -> CommandOption {
+        // NOTE: Content type and size restrictions are added by the `command` derive macro
+        CommandOption::new(CommandOptionType::Attachment)
+    }
+}
+
+// --- Resolved Types ---
+/// Looks up a value in the interaction's resolved data
+fn resolve<T: Clone>(
+    context: &ParseContext<'_>,
+    lookup: impl FnOnce(&InteractionDataResolved) -> Option<&T>,
+) -> Result<T> {
+    context
+        .resolved
+        .and_then(lookup)
+        .cloned()
+        .ok_or_else(|| anyhow!(Error::unresolved()))
+}
+
+impl ArgumentConverter for User {
+    fn convert(data: &CommandOptionValue, context: &ParseContext<'_>) -> Result<Self> {
+        if let CommandOptionValue::User(id) = data {
+            resolve(context, |resolved| resolved.users.get(id))
+        } else {
+            Err(anyhow!(Error::wrong_kind(CommandOptionType::User, data)))
+        }
+    }
+}
+
+impl ToOption for User {
+    fn to_option() -> CommandOption {
+        CommandOption::new(CommandOptionType::User)
+    }
+}
+
+/// A user along with their membership in the guild the command was used in
+#[derive(Debug, Clone)]
+pub struct ResolvedMember {
+    pub user: User,
+    pub member: InteractionMember,
+}
+
+impl ArgumentConverter for ResolvedMember {
+    fn convert(data: &CommandOptionValue, context: &ParseContext<'_>) -> Result<Self> {
+        if let CommandOptionValue::User(id) = data {
+            if context.guild_id.is_none() {
+                return Err(anyhow!(Error::guild_only()));
+            }
+            Ok(ResolvedMember {
+                user: resolve(context, |resolved| resolved.users.get(id))?,
+                member: resolve(context, |resolved| resolved.members.get(id))?,
+            })
+        } else {
+            Err(anyhow!(Error::wrong_kind(CommandOptionType::User, data)))
+        }
+    }
+}
+
+impl ToOption for ResolvedMember {
+    fn to_option() -> CommandOption {
+        CommandOption::new(CommandOptionType::User)
+    }
+}
+
+impl ArgumentConverter for Role {
+    fn convert(data: &CommandOptionValue, context: &ParseContext<'_>) -> Result<Self> {
+        if let CommandOptionValue::Role(id) = data {
+            resolve(context, |resolved| resolved.roles.get(id))
+        } else {
+            Err(anyhow!(Error::wrong_kind(CommandOptionType::Role, data)))
+        }
+    }
+}
+
+impl ToOption for Role {
+    fn to_option() -> CommandOption {
+        CommandOption::new(CommandOptionType::Role)
+    }
+}
+
+impl ArgumentConverter for InteractionChannel {
+    fn convert(data: &CommandOptionValue, context: &ParseContext<'_>) -> Result<Self> {
+        if let CommandOptionValue::Channel(id) = data {
+            resolve(context, |resolved| resolved.channels.get(id))
+        } else {
+            Err(anyhow!(Error::wrong_kind(CommandOptionType::Channel, data)))
+        }
+    }
+}
+
+impl ToOption for InteractionChannel {
+    fn to_option() -> CommandOption {
+        // NOTE: Channel types are filtered as a part of the `command` derive macro
+        CommandOption::new(CommandOptionType::Channel)
+    }
+}
+
+/// A user or role picked for a mentionable option
+#[derive(Debug, Clone)]
+#[allow(
+    clippy::large_enum_variant,
+    reason = "Mirrors the shape of the resolved data and is only built once per option"
+)]
+pub enum Mentionable {
+    /// A user, along with their membership if the command was used in a guild
+    User(User, Option<InteractionMember>),
+    Role(Role),
+}
+
+impl ArgumentConverter for Mentionable {
+    fn convert(data: &CommandOptionValue, context: &ParseContext<'_>) -> Result<Self> {
+        let CommandOptionValue::Mentionable(id) = data else {
+            return Err(anyhow!(Error::wrong_kind(
+                CommandOptionType::Mentionable,
+                data
+            )));
+        };
+        let resolved = context
+            .resolved
+            .ok_or_else(|| anyhow!(Error::unresolved()))?;
+
+        if let Some(user) = resolved.users.get(&id.cast()) {
+            let member = resolved.members.get(&id.cast()).cloned();
+            Ok(Mentionable::User(user.clone(), member))
+        } else if let Some(role) = resolved.roles.get(&id.cast()) {
+            Ok(Mentionable::Role(role.clone()))
+        } else {
+            Err(anyhow!(Error::unresolved()))
+        }
+    }
+}
+
+impl ToOption for Mentionable {
+    fn to_option() -> CommandOption {
+        CommandOption::new(CommandOptionType::Mentionable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds<T: ToOption>() -> (i64, i64) {
+        let option = T::to_option();
+        match (option.min_value, option.max_value) {
+            (
+                Some(InteractionCommandOptionValue::Integer(min)),
+                Some(InteractionCommandOptionValue::Integer(max)),
+            ) => (min, max),
+            bounds => panic!("expected integer bounds, got {bounds:?}"),
+        }
+    }
+
+    #[test]
+    fn integer_bounds_are_clamped_to_discord_range() {
+        assert_eq!(bounds::<i8>(), (i8::MIN.into(), i8::MAX.into()));
+        assert_eq!(bounds::<i16>(), (i16::MIN.into(), i16::MAX.into()));
+        assert_eq!(bounds::<i32>(), (i32::MIN.into(), i32::MAX.into()));
+        assert_eq!(bounds::<i64>(), (MIN_INTEGER_VALUE, MAX_INTEGER_VALUE));
+        assert_eq!(bounds::<i128>(), (MIN_INTEGER_VALUE, MAX_INTEGER_VALUE));
+        assert_eq!(bounds::<isize>(), (MIN_INTEGER_VALUE, MAX_INTEGER_VALUE));
+
+        assert_eq!(bounds::<u8>(), (0, u8::MAX.into()));
+        assert_eq!(bounds::<u16>(), (0, u16::MAX.into()));
+        assert_eq!(bounds::<u32>(), (0, u32::MAX.into()));
+        assert_eq!(bounds::<u64>(), (0, MAX_INTEGER_VALUE));
+        assert_eq!(bounds::<u128>(), (0, MAX_INTEGER_VALUE));
+        assert_eq!(bounds::<usize>(), (0, MAX_INTEGER_VALUE));
+    }
+}
@@ -11,11 +11,20 @@ use twilight_model::{
             CommandOptionChoice, CommandOptionType,
             CommandOptionValue as InteractionCommandOptionValue,
         },
-        interaction::application_command::CommandOptionValue,
+        interaction::{
+            Interaction, InteractionData, InteractionDataResolved,
+            application_command::CommandOptionValue,
+        },
     },
-    channel::ChannelType,
+    channel::{Attachment, ChannelType},
+    id::{Id, marker::GuildMarker},
 };
 
+/// The smallest value Discord accepts for an integer option (-2^53 + 1)
+pub const MIN_INTEGER_VALUE: i64 = -MAX_INTEGER_VALUE;
+/// The largest value Discord accepts for an integer option (2^53 - 1)
+pub const MAX_INTEGER_VALUE: i64 = (1 << 53) - 1;
+
 #[derive(Debug, Clone)]
 pub struct CommandOption {
     pub autocomplete: Option<bool>,
@@ -29,12 +38,196 @@ pub struct CommandOption {
     pub min_length: Option<u16>,
     pub min_value: Option<InteractionCommandOptionValue>,
     pub required: bool,
+    pub name_localizations: Option<HashMap<String, String>>,
+    pub description_localizations: Option<HashMap<String, String>>,
+    /// For attachment options, the allowed content types. Checked while parsing and not sent to
+    /// Discord.
+    pub content_types: Option<Vec<String>>,
+    /// For attachment options, the maximum size in bytes. Checked while parsing and not sent to
+    /// Discord.
+    pub max_size: Option<u64>,
+    /// For subcommands and subcommand groups, the nested options
+    pub options: Option<Vec<CommandOption>>,
+}
+
+/// Data from the interaction that converters can use to resolve option values
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseContext<'a> {
+    /// The users, roles, channels and attachments referenced by the options
+    pub resolved: Option<&'a InteractionDataResolved>,
+    /// The guild the command was used in, or `None` in DMs
+    pub guild_id: Option<Id<GuildMarker>>,
+}
+
+impl<'a> ParseContext<'a> {
+    pub fn new(resolved: Option<&'a InteractionDataResolved>) -> Self {
+        ParseContext {
+            resolved,
+            guild_id: None,
+        }
+    }
+
+    pub fn guild_id(mut self, guild_id: Id<GuildMarker>) -> Self {
+        self.guild_id = Some(guild_id);
+        self
+    }
+
+    /// Builds a context from the resolved data of an application command interaction
+    pub fn from_interaction(interaction: &'a Interaction) -> Self {
+        let context = match &interaction.data {
+            Some(InteractionData::ApplicationCommand(data)) => Self::new(data.resolved.as_ref()),
+            _ => Self::default(),
+        };
+        ParseContext {
+            guild_id: interaction.guild_id,
+            ..context
+        }
+    }
 }
 
+/// An error encountered while parsing a command option
+///
+/// Converters leave `option` empty; it is filled in by [`parse`] and [`validate`].
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("Invalid type for command argument")]
-    InvalidType,
+    #[error("Missing required option `{option}`")]
+    Missing { option: String },
+    #[error("Option `{option}` should be of type {expected:?} but was {got:?}")]
+    WrongKind {
+        option: String,
+        expected: CommandOptionType,
+        got: CommandOptionType,
+    },
+    #[error("Option `{option}` is out of range: {reason}")]
+    OutOfRange { option: String, reason: RangeError },
+    #[error("Option `{option}` must be one of: {}", allowed.join(", "))]
+    InvalidChoice {
+        option: String,
+        allowed: Vec<String>,
+    },
+    #[error("Option `{option}` could not be resolved")]
+    Unresolved { option: String },
+    #[error("Option `{option}` can only be used in a server")]
+    GuildOnly { option: String },
+    #[error("Option `{option}` must be a file of type {}", allowed.join(", "))]
+    InvalidContentType {
+        option: String,
+        content_type: Option<String>,
+        allowed: Vec<String>,
+    },
+    #[error("Expected a subcommand of `{option}`")]
+    MissingSubcommand { option: String },
+    #[error("Unknown subcommand `{option}`")]
+    UnknownSubcommand { option: String },
+    #[error("Option `{option}` is invalid: {message}")]
+    Custom { option: String, message: String },
+}
+
+/// The reason an option's value is out of range
+#[derive(Debug, thiserror::Error)]
+pub enum RangeError {
+    #[error("{value} does not fit in {ty}")]
+    Type { value: i64, ty: &'static str },
+    #[error("{value} is less than the minimum of {min}")]
+    BelowMinimum { value: f64, min: f64 },
+    #[error("{value} is greater than the maximum of {max}")]
+    AboveMaximum { value: f64, max: f64 },
+    #[error("value must be at least {min} characters long")]
+    TooShort { min: u16 },
+    #[error("value must be at most {max} characters long")]
+    TooLong { max: u16 },
+    #[error("file is {size} bytes, but at most {max} bytes are allowed")]
+    TooLarge { size: u64, max: u64 },
+}
+
+impl Error {
+    /// An error for a value of the wrong type
+    pub fn wrong_kind(expected: CommandOptionType, got: &CommandOptionValue) -> Self {
+        Error::WrongKind {
+            option: String::new(),
+            expected,
+            got: got.kind(),
+        }
+    }
+
+    /// An error for a value that is out of range
+    pub fn out_of_range(reason: RangeError) -> Self {
+        Error::OutOfRange {
+            option: String::new(),
+            reason,
+        }
+    }
+
+    /// An error for a value that is not one of the allowed choices, listed by name
+    pub fn invalid_choice(allowed: &[&str]) -> Self {
+        Error::InvalidChoice {
+            option: String::new(),
+            allowed: allowed.iter().map(|choice| choice.to_string()).collect(),
+        }
+    }
+
+    /// An error for a value missing from the interaction's resolved data
+    pub fn unresolved() -> Self {
+        Error::Unresolved {
+            option: String::new(),
+        }
+    }
+
+    /// An error for a value that requires a guild, used outside of one
+    pub fn guild_only() -> Self {
+        Error::GuildOnly {
+            option: String::new(),
+        }
+    }
+
+    /// An error with a custom message
+    pub fn custom(message: impl Into<String>) -> Self {
+        Error::Custom {
+            option: String::new(),
+            message: message.into(),
+        }
+    }
+
+    /// Sets the name of the option this error occurred for
+    pub fn with_option(mut self, name: &str) -> Self {
+        let (Error::Missing { option }
+        | Error::WrongKind { option, .. }
+        | Error::OutOfRange { option, .. }
+        | Error::InvalidChoice { option, .. }
+        | Error::Unresolved { option }
+        | Error::GuildOnly { option }
+        | Error::MissingSubcommand { option }
+        | Error::UnknownSubcommand { option }
+        | Error::InvalidContentType { option, .. }
+        | Error::Custom { option, .. }) = &mut self;
+        *option = name.to_string();
+        self
+    }
+
+    /// The name of the option this error occurred for
+    pub fn option(&self) -> &str {
+        match self {
+            Error::Missing { option }
+            | Error::WrongKind { option, .. }
+            | Error::OutOfRange { option, .. }
+            | Error::InvalidChoice { option, .. }
+            | Error::Unresolved { option }
+            | Error::GuildOnly { option }
+            | Error::MissingSubcommand { option }
+            | Error::UnknownSubcommand { option }
+            | Error::InvalidContentType { option, .. }
+            | Error::Custom { option, .. } => option,
+        }
+    }
+}
+
+/// Attaches an option name to a conversion error, wrapping errors from other sources in
+/// [`Error::Custom`]
+fn attach_option(error: anyhow::Error, name: &str) -> anyhow::Error {
+    match error.downcast::<Error>() {
+        Ok(error) => anyhow!(error.with_option(name)),
+        Err(error) => anyhow!(Error::custom(error.to_string()).with_option(name)),
+    }
 }
 
 pub trait ToOption {
@@ -42,28 +235,30 @@ pub trait ToOption {
 }
 
 pub trait OptionalArgumentConverter: Sized {
-    fn convert(data: Option<&CommandOptionValue>) -> Result<Self>;
+    fn convert(data: Option<&CommandOptionValue>, context: &ParseContext<'_>) -> Result<Self>;
 }
 
 pub trait ArgumentConverter: Sized {
-    fn convert(data: &CommandOptionValue) -> Result<Self>;
+    fn convert(data: &CommandOptionValue, context: &ParseContext<'_>) -> Result<Self>;
 }
 
 impl<T: OptionalArgumentConverter> OptionalArgumentConverter for Option<T> {
-    fn convert(data: Option<&CommandOptionValue>) -> Result<Self> {
+    fn convert(data: Option<&CommandOptionValue>, context: &ParseContext<'_>) -> Result<Self> {
         match data {
-            Some(_) => Ok(Some(T::convert(data)?)),
+            Some(_) => Ok(Some(T::convert(data, context)?)),
             None => Ok(None),
         }
     }
 }
 
 impl<T: ArgumentConverter> OptionalArgumentConverter for T {
-    fn convert(data: Option<&CommandOptionValue>) -> Result<Self> {
+    fn convert(data: Option<&CommandOptionValue>, context: &ParseContext<'_>) -> Result<Self> {
         if let Some(value) = data {
-            T::convert(value)
+            T::convert(value, context)
         } else {
-            Err(anyhow!(Error::InvalidType))
+            Err(anyhow!(Error::Missing {
+                option: String::new()
+            }))
         }
     }
 }
@@ -82,6 +277,11 @@ impl CommandOption {
             min_length: None,
             min_value: None,
             required: true,
+            name_localizations: None,
+            description_localizations: None,
+            content_types: None,
+            max_size: None,
+            options: None,
         }
     }
 
@@ -128,6 +328,11 @@ impl CommandOption {
         self
     }
 
+    pub fn options(mut self, options: Vec<CommandOption>) -> Self {
+        self.options = Some(options);
+        self
+    }
+
     pub fn required(mut self, required: bool) -> Self {
         self.required = required;
         self
@@ -142,13 +347,171 @@ impl CommandOption {
         self.description = Some(description.to_string());
         self
     }
+
+    pub fn name_localizations(mut self, name_localizations: HashMap<String, String>) -> Self {
+        self.name_localizations = Some(name_localizations);
+        self
+    }
+
+    pub fn name_localization(mut self, locale: &str, name: &str) -> Self {
+        self.name_localizations
+            .get_or_insert_with(HashMap::new)
+            .insert(locale.to_string(), name.to_string());
+        self
+    }
+
+    pub fn description_localizations(
+        mut self,
+        description_localizations: HashMap<String, String>,
+    ) -> Self {
+        self.description_localizations = Some(description_localizations);
+        self
+    }
+
+    pub fn description_localization(mut self, locale: &str, description: &str) -> Self {
+        self.description_localizations
+            .get_or_insert_with(HashMap::new)
+            .insert(locale.to_string(), description.to_string());
+        self
+    }
+
+    pub fn content_types(mut self, content_types: Vec<String>) -> Self {
+        self.content_types = Some(content_types);
+        self
+    }
+
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Checks a received value against this option's constraints
+    pub fn validate(&self, value: &CommandOptionValue, context: &ParseContext<'_>) -> Result<()> {
+        match value {
+            CommandOptionValue::String(value) => {
+                let length = value.chars().count();
+                if let Some(min) = self.min_length
+                    && length < min as usize
+                {
+                    return Err(self.error(RangeError::TooShort { min }));
+                }
+                if let Some(max) = self.max_length
+                    && length > max as usize
+                {
+                    return Err(self.error(RangeError::TooLong { max }));
+                }
+                Ok(())
+            }
+            CommandOptionValue::Integer(value) => self.validate_number(*value as f64),
+            CommandOptionValue::Number(value) => self.validate_number(*value),
+            CommandOptionValue::Attachment(id) => {
+                match context
+                    .resolved
+                    .and_then(|resolved| resolved.attachments.get(id))
+                {
+                    Some(attachment) => self.validate_attachment(attachment),
+                    None => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_attachment(&self, attachment: &Attachment) -> Result<()> {
+        if let Some(max) = self.max_size
+            && attachment.size > max
+        {
+            return Err(self.error(RangeError::TooLarge {
+                size: attachment.size,
+                max,
+            }));
+        }
+        if let Some(allowed) = &self.content_types {
+            let matches = attachment
+                .content_type
+                .as_deref()
+                .is_some_and(|content_type| {
+                    allowed
+                        .iter()
+                        .any(|pattern| content_type_matches(pattern, content_type))
+                });
+            if !matches {
+                return Err(anyhow!(Error::InvalidContentType {
+                    option: self.name.clone().unwrap_or_default(),
+                    content_type: attachment.content_type.clone(),
+                    allowed: allowed.clone(),
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_number(&self, value: f64) -> Result<()> {
+        if let Some(min) = self.min_value.map(bound_to_f64)
+            && value < min
+        {
+            return Err(self.error(RangeError::BelowMinimum { value, min }));
+        }
+        if let Some(max) = self.max_value.map(bound_to_f64)
+            && value > max
+        {
+            return Err(self.error(RangeError::AboveMaximum { value, max }));
+        }
+        Ok(())
+    }
+
+    fn error(&self, reason: RangeError) -> anyhow::Error {
+        anyhow!(Error::out_of_range(reason).with_option(self.name.as_deref().unwrap_or_default()))
+    }
+}
+
+/// Matches a content type against a pattern such as `image/png`, `image/*` or `*/*`
+///
+/// Parameters such as `; charset=utf-8` are ignored, and both are compared case-insensitively.
+fn content_type_matches(pattern: &str, content_type: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    let media_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match pattern.strip_suffix("/*") {
+        Some("*") => media_type.contains('/'),
+        Some(prefix) => media_type
+            .split_once('/')
+            .is_some_and(|(kind, _)| kind == prefix),
+        None => pattern == media_type,
+    }
+}
+
+fn bound_to_f64(bound: InteractionCommandOptionValue) -> f64 {
+    match bound {
+        InteractionCommandOptionValue::Integer(value) => value as f64,
+        InteractionCommandOptionValue::Number(value) => value,
+    }
+}
+
+/// Validates the received options against the constraints of the command's options
+pub fn validate(
+    options: &HashMap<String, CommandOptionValue>,
+    command_options: &[CommandOption],
+    context: &ParseContext<'_>,
+) -> Result<()> {
+    for option in command_options {
+        if let Some(value) = option.name.as_ref().and_then(|name| options.get(name)) {
+            option.validate(value, context)?;
+        }
+    }
+    Ok(())
 }
 
 pub fn parse<T: OptionalArgumentConverter>(
     options: &HashMap<String, CommandOptionValue>,
     name: &str,
+    context: &ParseContext<'_>,
 ) -> Result<T> {
-    T::convert(options.get(name))
+    T::convert(options.get(name), context).map_err(|e| attach_option(e, name))
 }
 
 impl<T: ToOption> ToOption for Option<T> {
@@ -170,10 +533,42 @@ impl From<CommandOption> for twilight_model::application::command::CommandOption
             max_value: option.max_value,
             min_length: option.min_length,
             min_value: option.min_value,
-            required: Some(option.required),
-            description_localizations: None,
-            name_localizations: None,
-            options: None,
+            required: (!matches!(
+                option.kind,
+                CommandOptionType::SubCommand | CommandOptionType::SubCommandGroup
+            ))
+            .then_some(option.required),
+            description_localizations: option.description_localizations,
+            name_localizations: option.name_localizations,
+            options: option
+                .options
+                .map(|options| options.into_iter().map(Into::into).collect()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_type_matches_exact_and_wildcard_patterns() {
+        assert!(content_type_matches("image/png", "image/png"));
+        assert!(!content_type_matches("image/png", "image/jpeg"));
+        assert!(content_type_matches("image/*", "image/webp"));
+        assert!(!content_type_matches("image/*", "video/mp4"));
+        assert!(!content_type_matches("image/*", "image"));
+        assert!(content_type_matches("*/*", "application/pdf"));
+        assert!(!content_type_matches("*/*", ""));
+    }
+
+    #[test]
+    fn content_type_matches_ignores_parameters_and_case() {
+        assert!(content_type_matches(
+            "text/plain",
+            "text/plain; charset=utf-8"
+        ));
+        assert!(content_type_matches("text/*", "TEXT/Plain;charset=UTF-8"));
+        assert!(content_type_matches("Image/PNG", "image/png"));
+    }
+}
@@ -0,0 +1,141 @@
+use std::{
+    collections::HashSet,
+    future::{Ready, ready},
+    pin::Pin,
+    sync::Arc,
+};
+
+use twilight_model::{
+    application::{command::CommandType, interaction::Interaction},
+    id::{
+        Id,
+        marker::{RoleMarker, UserMarker},
+    },
+};
+
+/// The outcome of a [`Check`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckResult {
+    /// The command may run
+    Allow,
+    /// The command may not run, with a reason shown to the user
+    Deny(String),
+}
+
+impl CheckResult {
+    pub fn deny(reason: impl Into<String>) -> Self {
+        CheckResult::Deny(reason.into())
+    }
+}
+
+/// The command a check is run for
+#[derive(Debug, Clone)]
+pub struct CommandMetadata {
+    /// The full name of the invoked command, including subcommands, eg. `admin users ban`
+    pub name: String,
+    /// The type of the command
+    pub kind: CommandType,
+}
+
+/// A precondition that must hold for a command to run
+pub trait Check<S>: Send + Sync {
+    fn check(
+        &self,
+        interaction: Arc<Interaction>,
+        state: Arc<S>,
+        command: CommandMetadata,
+    ) -> Pin<Box<dyn Future<Output = CheckResult> + Send>>;
+}
+
+impl<S, F, Fut> Check<S> for F
+where
+    F: Fn(Arc<Interaction>, Arc<S>, CommandMetadata) -> Fut + Send + Sync,
+    Fut: Future<Output = CheckResult> + Send + 'static,
+{
+    fn check(
+        &self,
+        interaction: Arc<Interaction>,
+        state: Arc<S>,
+        command: CommandMetadata,
+    ) -> Pin<Box<dyn Future<Output = CheckResult> + Send>> {
+        Box::pin(self(interaction, state, command))
+    }
+}
+
+/// Boxes a check, inferring the state of generic check functions such as [`guild_only`]
+#[doc(hidden)]
+pub fn boxed<S, C>(check: C) -> Box<dyn Check<S>>
+where
+    C: Check<S> + 'static,
+{
+    Box::new(check)
+}
+
+/// Runs checks in order, returning the reason of the first one that denies the command
+#[cfg(feature = "executor")]
+pub(crate) async fn run<S>(
+    checks: &[&dyn Check<S>],
+    interaction: &Arc<Interaction>,
+    state: &Arc<S>,
+    command: &CommandMetadata,
+) -> Option<String> {
+    for check in checks {
+        let result = check
+            .check(Arc::clone(interaction), Arc::clone(state), command.clone())
+            .await;
+        if let CheckResult::Deny(reason) = result {
+            return Some(reason);
+        }
+    }
+    None
+}
+
+/// Only allows the command to be used in a server
+pub fn guild_only<S>(
+    interaction: Arc<Interaction>,
+    _state: Arc<S>,
+    _command: CommandMetadata,
+) -> Ready<CheckResult> {
+    ready(match interaction.guild_id {
+        Some(_) => CheckResult::Allow,
+        None => CheckResult::deny("This command can only be used in a server"),
+    })
+}
+
+/// Only allows the command to be used in direct messages
+pub fn dm_only<S>(
+    interaction: Arc<Interaction>,
+    _state: Arc<S>,
+    _command: CommandMetadata,
+) -> Ready<CheckResult> {
+    ready(match interaction.guild_id {
+        Some(_) => CheckResult::deny("This command can only be used in direct messages"),
+        None => CheckResult::Allow,
+    })
+}
+
+/// Only allows the given users to use the command
+pub fn owner_only<S>(owners: impl IntoIterator<Item = Id<UserMarker>>) -> impl Check<S> {
+    let owners = owners.into_iter().collect::<HashSet<_>>();
+    move |interaction: Arc<Interaction>, _state: Arc<S>, _command: CommandMetadata| {
+        ready(match interaction.author_id() {
+            Some(user) if owners.contains(&user) => CheckResult::Allow,
+            _ => CheckResult::deny("This command can only be used by the bot's owners"),
+        })
+    }
+}
+
+/// Only allows members with the given role to use the command
+pub fn has_role<S>(role: Id<RoleMarker>) -> impl Check<S> {
+    move |interaction: Arc<Interaction>, _state: Arc<S>, _command: CommandMetadata| {
+        let has_role = interaction
+            .member
+            .as_ref()
+            .is_some_and(|member| member.roles.contains(&role));
+        ready(if has_role {
+            CheckResult::Allow
+        } else {
+            CheckResult::deny(format!("You need the <@&{role}> role to use this command"))
+        })
+    }
+}
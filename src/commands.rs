@@ -1,16 +1,152 @@
-use anyhow::Result;
-use twilight_model::application::interaction::application_command::CommandDataOption;
+use std::collections::HashMap;
 
-use crate::arguments::CommandOption;
+use anyhow::{Result, anyhow};
+use twilight_model::{
+    application::{
+        command::CommandOptionType,
+        interaction::{
+            InteractionContextType,
+            application_command::{CommandDataOption, CommandOptionValue},
+        },
+    },
+    guild::Permissions,
+    oauth::ApplicationIntegrationType,
+};
+
+use crate::{
+    arguments::{CommandOption, Error, ParseContext},
+    checks::Check,
+};
 
 pub trait Command: Send + Sync + 'static + Sized {
     /// Gets a list of options for this command
     fn options() -> Vec<CommandOption>;
     /// Converts a Vec of `CommandDataOption` into this command
-    fn from_command_data(data: Vec<CommandDataOption>) -> Result<Self>;
+    fn from_command_data(data: Vec<CommandDataOption>, context: &ParseContext<'_>) -> Result<Self>;
 
     /// The command description as rendered in the discord client
     fn description() -> &'static str;
     /// The command's name
     fn name() -> &'static str;
+
+    /// Localized names of the command, keyed by locale
+    ///
+    /// For subcommands, these localize the last segment of the name.
+    fn name_localizations() -> Option<HashMap<String, String>> {
+        None
+    }
+    /// Localized descriptions of the command, keyed by locale
+    fn description_localizations() -> Option<HashMap<String, String>> {
+        None
+    }
+
+    /// Where the command can be used, or `None` to allow all contexts
+    ///
+    /// Only used for top-level commands, so it is ignored for commands used as a subcommand in
+    /// a command enum.
+    fn contexts() -> Option<Vec<InteractionContextType>> {
+        None
+    }
+    /// How the app must be installed for the command to be available, or `None` to use the
+    /// app's default
+    ///
+    /// Only used for top-level commands, so it is ignored for commands used as a subcommand in
+    /// a command enum.
+    fn integration_types() -> Option<Vec<ApplicationIntegrationType>> {
+        None
+    }
+    /// Permissions members need to see and use the command by default, or `None` to allow
+    /// everyone
+    ///
+    /// Guild admins can override these. Only used for top-level commands, so they are ignored
+    /// for commands used as a subcommand in a command enum.
+    fn default_member_permissions() -> Option<Permissions> {
+        None
+    }
+    /// Whether the command is age-restricted
+    ///
+    /// Only used for top-level commands, so it is ignored for commands used as a subcommand in a
+    /// command enum.
+    fn nsfw() -> bool {
+        false
+    }
+
+    /// Permissions the invoking member must have, checked by the executor before the handler
+    /// runs
+    ///
+    /// Unlike the default member permissions, these can't be overridden by guild admins. They are
+    /// not checked before autocomplete handlers.
+    fn required_permissions(&self) -> Permissions {
+        Permissions::empty()
+    }
+    /// Permissions the bot must have in the channel, checked by the executor before the handler
+    /// runs
+    fn required_bot_permissions(&self) -> Permissions {
+        Permissions::empty()
+    }
+}
+
+/// Checks that must pass for a command to run, for an executor with state `S`
+///
+/// Derived along with `Command`, for every state unless the command's checks use a specific one,
+/// set with `#[command(state = ...)]`.
+pub trait CommandChecks<S>: Command {
+    /// The checks, run by the executor after the permission checks
+    ///
+    /// They don't run before autocomplete handlers, as the command can't be parsed from the
+    /// incomplete options.
+    fn checks(&self) -> Vec<Box<dyn Check<S>>> {
+        Vec::new()
+    }
+}
+
+/// The name of a subcommand or subcommand group within its parent, ie. the last segment of its
+/// name
+pub fn subcommand_name<C: Command>() -> &'static str {
+    C::name().rsplit(' ').next().unwrap_or_default()
+}
+
+/// Describes a subcommand or subcommand group as an option of its parent
+///
+/// Commands whose options are all subcommands are described as subcommand groups.
+pub fn subcommand_option<C: Command>() -> CommandOption {
+    let options = C::options();
+    let kind = if !options.is_empty()
+        && options
+            .iter()
+            .all(|option| option.kind == CommandOptionType::SubCommand)
+    {
+        CommandOptionType::SubCommandGroup
+    } else {
+        CommandOptionType::SubCommand
+    };
+
+    let mut option = CommandOption::new(kind)
+        .name(subcommand_name::<C>())
+        .description(C::description())
+        .options(options);
+    option.name_localizations = C::name_localizations();
+    option.description_localizations = C::description_localizations();
+    option
+}
+
+/// Takes the invoked subcommand or subcommand group out of the options of the command `parent`,
+/// returning its name and options
+pub fn take_subcommand(
+    parent: &str,
+    options: Vec<CommandDataOption>,
+) -> Result<(String, Vec<CommandDataOption>)> {
+    let missing = || {
+        anyhow!(Error::MissingSubcommand {
+            option: parent.to_string()
+        })
+    };
+
+    let option = options.into_iter().next().ok_or_else(missing)?;
+    match option.value {
+        CommandOptionValue::SubCommand(options) | CommandOptionValue::SubCommandGroup(options) => {
+            Ok((option.name, options))
+        }
+        _ => Err(missing()),
+    }
 }
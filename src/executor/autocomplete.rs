@@ -0,0 +1,87 @@
+use std::{collections::HashMap, pin::Pin, sync::Arc};
+
+use anyhow::Result;
+use twilight_model::application::{
+    command::{CommandOptionChoice, CommandOptionType},
+    interaction::{
+        Interaction,
+        application_command::{CommandDataOption, CommandOptionValue},
+    },
+};
+
+/// The maximum number of choices Discord accepts in an autocomplete response
+pub const MAX_AUTOCOMPLETE_CHOICES: usize = 25;
+
+type AutocompleteResult = Result<Vec<CommandOptionChoice>>;
+
+/// An autocomplete request for a single option of a command
+#[derive(Debug, Clone)]
+pub struct AutocompleteRequest {
+    /// The name of the option being completed
+    pub option: String,
+    /// The partial value the user has typed so far
+    pub value: String,
+    /// The type of the option being completed
+    pub kind: CommandOptionType,
+    /// The other options the user has already filled in
+    pub options: HashMap<String, CommandOptionValue>,
+}
+
+impl AutocompleteRequest {
+    /// Builds a request from the options of an autocomplete interaction, returning `None` if no
+    /// option is focused
+    ///
+    /// Options of an invoked subcommand are looked up inside of it.
+    pub(crate) fn from_options(options: Vec<CommandDataOption>) -> Option<Self> {
+        let mut focused = None;
+        let mut filled = HashMap::new();
+
+        for option in options {
+            match option.value {
+                CommandOptionValue::SubCommand(options)
+                | CommandOptionValue::SubCommandGroup(options) => {
+                    return Self::from_options(options);
+                }
+                CommandOptionValue::Focused(value, kind) => {
+                    focused = Some((option.name, value, kind))
+                }
+                value => {
+                    filled.insert(option.name, value);
+                }
+            }
+        }
+
+        let (option, value, kind) = focused?;
+        Some(AutocompleteRequest {
+            option,
+            value,
+            kind,
+            options: filled,
+        })
+    }
+}
+
+/// Provides autocomplete choices for a command option
+pub trait Autocomplete<S>: Send + Sync {
+    fn complete(
+        &self,
+        request: AutocompleteRequest,
+        interaction: Arc<Interaction>,
+        state: Arc<S>,
+    ) -> Pin<Box<dyn Future<Output = AutocompleteResult> + Send>>;
+}
+
+impl<S, F, Fut> Autocomplete<S> for F
+where
+    F: Fn(AutocompleteRequest, Arc<Interaction>, Arc<S>) -> Fut + Send + Sync,
+    Fut: Future<Output = AutocompleteResult> + Send + 'static,
+{
+    fn complete(
+        &self,
+        request: AutocompleteRequest,
+        interaction: Arc<Interaction>,
+        state: Arc<S>,
+    ) -> Pin<Box<dyn Future<Output = AutocompleteResult> + Send>> {
+        Box::pin(self(request, interaction, state))
+    }
+}
@@ -1,19 +1,37 @@
-use std::{collections::HashMap, pin::Pin, sync::Arc};
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    pin::Pin,
+    sync::Arc,
+};
 
 use twilight_model::{
     application::{
         command::{Command, CommandType},
-        interaction::{Interaction, InteractionContextType, InteractionData},
+        interaction::{
+            Interaction, InteractionContextType, InteractionData, InteractionDataResolved,
+            InteractionMember,
+        },
     },
-    channel::message::MessageFlags,
+    channel::{Message, message::MessageFlags},
+    guild::Permissions,
     http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{Id, marker::GenericMarker},
     oauth::ApplicationIntegrationType,
+    user::User,
 };
 use twilight_util::builder::{
     command::CommandBuilder,
     message::{ContainerBuilder, TextDisplayBuilder},
 };
 
+use crate::{
+    checks::{self, Check, CommandMetadata},
+    executor::{
+        layer::{Layer, Next},
+        slash::ephemeral_response,
+    },
+};
+
 type InteractionResult = anyhow::Result<InteractionResponse>;
 
 type AsyncHandler<T> = Box<
@@ -22,14 +40,102 @@ type AsyncHandler<T> = Box<
         + Sync,
 >;
 
+/// The reason the target of a context menu command could not be resolved
+#[derive(Debug, thiserror::Error)]
+pub enum TargetError {
+    #[error("Interaction has no target")]
+    MissingTarget,
+    #[error("Target `{0}` could not be resolved")]
+    Unresolved(Id<GenericMarker>),
+}
+
+/// A registered context menu command.
+///
+/// Returned when registering a command, to restrict where it is available.
+pub struct ContextCommand<T> {
+    handler: Arc<AsyncHandler<T>>,
+    contexts: Vec<InteractionContextType>,
+    integration_types: Vec<ApplicationIntegrationType>,
+    default_member_permissions: Option<Permissions>,
+    nsfw: bool,
+    checks: Vec<Arc<dyn Check<T>>>,
+}
+
+impl<T> ContextCommand<T> {
+    /// Sets where the command can be used. Defaults to all contexts.
+    pub fn contexts(&mut self, contexts: Vec<InteractionContextType>) -> &mut Self {
+        self.contexts = contexts;
+        self
+    }
+
+    /// Sets how the app must be installed for the command to be available. Defaults to both
+    /// guild and user installs.
+    pub fn integration_types(
+        &mut self,
+        integration_types: Vec<ApplicationIntegrationType>,
+    ) -> &mut Self {
+        self.integration_types = integration_types;
+        self
+    }
+
+    /// Sets the permissions members need to see and use the command by default.
+    pub fn default_member_permissions(&mut self, permissions: Permissions) -> &mut Self {
+        self.default_member_permissions = Some(permissions);
+        self
+    }
+
+    /// Sets whether the command is age-restricted.
+    pub fn nsfw(&mut self, nsfw: bool) -> &mut Self {
+        self.nsfw = nsfw;
+        self
+    }
+
+    /// Adds a check that must pass for the command to run.
+    pub fn check<C>(&mut self, check: C) -> &mut Self
+    where
+        C: Check<T> + 'static,
+    {
+        self.checks.push(Arc::new(check));
+        self
+    }
+}
+
 /// Commands that can be used via a context menu.
+///
+/// User and message commands are registered separately, so both kinds can share a name.
 pub struct ContextCommands<T> {
-    commands: HashMap<String, Arc<AsyncHandler<T>>>,
+    commands: HashMap<(String, CommandType), ContextCommand<T>>,
+    checks: Vec<Arc<dyn Check<T>>>,
+    layers: Vec<Arc<dyn Layer<T>>>,
 }
 
 impl<S> ContextCommands<S> {
-    /// Registers a context menu command.
-    pub fn register<F, Fut>(&mut self, command: &str, handler: F)
+    /// Adds a check that must pass for any context menu command to run.
+    pub fn check<C>(&mut self, check: C)
+    where
+        C: Check<S> + 'static,
+    {
+        self.checks.push(Arc::new(check));
+    }
+
+    /// Wraps the execution of every context menu command in a layer.
+    ///
+    /// Layers run in the order they were added, the first one being the outermost, and before
+    /// any checks.
+    pub fn layer<L>(&mut self, layer: L)
+    where
+        L: Layer<S> + 'static,
+    {
+        self.layers.push(Arc::new(layer));
+    }
+
+    /// Registers a context menu command of the given type.
+    pub fn register<F, Fut>(
+        &mut self,
+        command: &str,
+        kind: CommandType,
+        handler: F,
+    ) -> &mut ContextCommand<S>
     where
         F: Fn(Arc<Interaction>, Arc<S>) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = InteractionResult> + Send + 'static,
@@ -38,12 +144,96 @@ impl<S> ContextCommands<S> {
             Box::pin(handler(interaction, state))
                 as Pin<Box<dyn Future<Output = InteractionResult> + Send>>
         });
-        self.commands.insert(command.to_string(), Arc::new(handler));
+        let context_command = ContextCommand {
+            handler: Arc::new(handler),
+            contexts: vec![
+                InteractionContextType::Guild,
+                InteractionContextType::BotDm,
+                InteractionContextType::PrivateChannel,
+            ],
+            integration_types: vec![
+                ApplicationIntegrationType::UserInstall,
+                ApplicationIntegrationType::GuildInstall,
+            ],
+            default_member_permissions: None,
+            nsfw: false,
+            checks: Vec::new(),
+        };
+        match self.commands.entry((command.to_string(), kind)) {
+            Entry::Occupied(mut entry) => {
+                entry.insert(context_command);
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => entry.insert(context_command),
+        }
+    }
+
+    /// Registers a context menu command shown when right-clicking a user.
+    pub fn register_user<F, Fut>(&mut self, command: &str, handler: F) -> &mut ContextCommand<S>
+    where
+        F: Fn(Arc<Interaction>, Arc<S>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = InteractionResult> + Send + 'static,
+    {
+        self.register(command, CommandType::User, handler)
+    }
+
+    /// Registers a context menu command shown when right-clicking a message.
+    pub fn register_message<F, Fut>(&mut self, command: &str, handler: F) -> &mut ContextCommand<S>
+    where
+        F: Fn(Arc<Interaction>, Arc<S>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = InteractionResult> + Send + 'static,
+    {
+        self.register(command, CommandType::Message, handler)
+    }
+
+    /// Registers a user command whose handler receives the targeted user, along with their
+    /// membership if the command was used in a guild.
+    pub fn register_user_target<F, Fut>(
+        &mut self,
+        command: &str,
+        handler: F,
+    ) -> &mut ContextCommand<S>
+    where
+        F: Fn(User, Option<InteractionMember>, Arc<Interaction>, Arc<S>) -> Fut
+            + Send
+            + Sync
+            + 'static,
+        Fut: Future<Output = InteractionResult> + Send + 'static,
+    {
+        self.register_user(command, move |interaction, state| {
+            let fut = resolve_target(&interaction, |resolved, id| {
+                let user = resolved.users.get(&id.cast())?.clone();
+                Some((user, resolved.members.get(&id.cast()).cloned()))
+            })
+            .map(|(user, member)| handler(user, member, interaction, state));
+            async move { fut?.await }
+        })
+    }
+
+    /// Registers a message command whose handler receives the targeted message.
+    pub fn register_message_target<F, Fut>(
+        &mut self,
+        command: &str,
+        handler: F,
+    ) -> &mut ContextCommand<S>
+    where
+        F: Fn(Message, Arc<Interaction>, Arc<S>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = InteractionResult> + Send + 'static,
+    {
+        self.register_message(command, move |interaction, state| {
+            let fut = resolve_target(&interaction, |resolved, id| {
+                resolved.messages.get(&id.cast()).cloned()
+            })
+            .map(|message| handler(message, interaction, state));
+            async move { fut?.await }
+        })
     }
 
     /// Gets a registered context menu command.
-    pub fn get(&self, name: &str) -> Option<&Arc<AsyncHandler<S>>> {
-        self.commands.get(name)
+    pub fn get(&self, name: &str, kind: CommandType) -> Option<&Arc<AsyncHandler<S>>> {
+        self.commands
+            .get(&(name.to_string(), kind))
+            .map(|command| &command.handler)
     }
 
     /// Executes a context menu command if it exists.
@@ -51,11 +241,39 @@ impl<S> ContextCommands<S> {
         &self,
         interaction: Arc<Interaction>,
         state: Arc<S>,
-    ) -> Option<InteractionResponse> {
-        if let Some(InteractionData::ApplicationCommand(ref command)) = interaction.data
-            && let Some(handler) = self.get(&command.name)
+    ) -> Option<InteractionResponse>
+    where
+        S: Send + Sync + 'static,
+    {
+        if let Some(InteractionData::ApplicationCommand(ref data)) = interaction.data
+            && let Some(context_command) = self.commands.get(&(data.name.clone(), data.kind))
         {
-            Some((handler)(interaction, state).await.unwrap_or_else(|e| {
+            let command = CommandMetadata {
+                name: data.name.clone(),
+                kind: data.kind,
+            };
+            let command_checks = self
+                .checks
+                .iter()
+                .chain(&context_command.checks)
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let handler = Arc::clone(&context_command.handler);
+            let endpoint_command = command.clone();
+            let next = Next::new(&self.layers, command, move |interaction, state| {
+                Box::pin(async move {
+                    let command_checks = command_checks.iter().map(Arc::as_ref).collect::<Vec<_>>();
+                    let denied =
+                        checks::run(&command_checks, &interaction, &state, &endpoint_command).await;
+                    if let Some(reason) = denied {
+                        return Ok(ephemeral_response(reason));
+                    }
+                    (handler)(interaction, state).await
+                })
+            });
+
+            Some(next.run(interaction, state).await.unwrap_or_else(|e| {
                 let container = ContainerBuilder::new()
                     .accent_color(Some(0xFF0000))
                     .component(TextDisplayBuilder::new(format!("An error occurred: {}", e)).build())
@@ -75,23 +293,36 @@ impl<S> ContextCommands<S> {
     }
 }
 
+/// Looks up the target of a context menu command in the interaction's resolved data
+fn resolve_target<T>(
+    interaction: &Interaction,
+    lookup: impl FnOnce(&InteractionDataResolved, Id<GenericMarker>) -> Option<T>,
+) -> Result<T, TargetError> {
+    let Some(InteractionData::ApplicationCommand(command)) = &interaction.data else {
+        return Err(TargetError::MissingTarget);
+    };
+    let id = command.target_id.ok_or(TargetError::MissingTarget)?;
+    command
+        .resolved
+        .as_ref()
+        .and_then(|resolved| lookup(resolved, id))
+        .ok_or(TargetError::Unresolved(id))
+}
+
 impl<S> From<&ContextCommands<S>> for Vec<Command> {
     fn from(context_commands: &ContextCommands<S>) -> Vec<Command> {
         context_commands
             .commands
-            .keys()
-            .map(|name| {
-                CommandBuilder::new(name, "", CommandType::Message)
-                    .integration_types([
-                        ApplicationIntegrationType::UserInstall,
-                        ApplicationIntegrationType::GuildInstall,
-                    ])
-                    .contexts(vec![
-                        InteractionContextType::Guild,
-                        InteractionContextType::BotDm,
-                        InteractionContextType::PrivateChannel,
-                    ])
-                    .build()
+            .iter()
+            .map(|((name, kind), command)| {
+                let mut builder = CommandBuilder::new(name, "", *kind)
+                    .integration_types(command.integration_types.clone())
+                    .contexts(command.contexts.clone())
+                    .nsfw(command.nsfw);
+                if let Some(permissions) = command.default_member_permissions {
+                    builder = builder.default_member_permissions(permissions);
+                }
+                builder.build()
             })
             .collect()
     }
@@ -104,6 +335,8 @@ where
     fn default() -> Self {
         Self {
             commands: HashMap::new(),
+            checks: Vec::new(),
+            layers: Vec::new(),
         }
     }
 }
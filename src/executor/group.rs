@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use twilight_model::{
+    application::interaction::InteractionContextType, guild::Permissions,
+    oauth::ApplicationIntegrationType,
+};
+
+/// Metadata for a parent command or subcommand group, which has no handler of its own
+///
+/// Default member permissions, contexts, integration types and the NSFW flag are only used for
+/// top-level commands, as Discord does not support them on subcommand groups.
+#[derive(Debug, Clone)]
+pub struct CommandGroup {
+    pub description: String,
+    pub name_localizations: Option<HashMap<String, String>>,
+    pub description_localizations: Option<HashMap<String, String>>,
+    pub default_member_permissions: Option<Permissions>,
+    pub contexts: Option<Vec<InteractionContextType>>,
+    pub integration_types: Option<Vec<ApplicationIntegrationType>>,
+    pub nsfw: bool,
+}
+
+impl CommandGroup {
+    pub fn new(description: &str) -> Self {
+        CommandGroup {
+            description: description.to_string(),
+            name_localizations: None,
+            description_localizations: None,
+            default_member_permissions: None,
+            contexts: None,
+            integration_types: None,
+            nsfw: false,
+        }
+    }
+
+    pub fn name_localizations(mut self, name_localizations: HashMap<String, String>) -> Self {
+        self.name_localizations = Some(name_localizations);
+        self
+    }
+
+    pub fn name_localization(mut self, locale: &str, name: &str) -> Self {
+        self.name_localizations
+            .get_or_insert_with(HashMap::new)
+            .insert(locale.to_string(), name.to_string());
+        self
+    }
+
+    pub fn description_localizations(
+        mut self,
+        description_localizations: HashMap<String, String>,
+    ) -> Self {
+        self.description_localizations = Some(description_localizations);
+        self
+    }
+
+    pub fn description_localization(mut self, locale: &str, description: &str) -> Self {
+        self.description_localizations
+            .get_or_insert_with(HashMap::new)
+            .insert(locale.to_string(), description.to_string());
+        self
+    }
+
+    pub fn default_member_permissions(mut self, permissions: Permissions) -> Self {
+        self.default_member_permissions = Some(permissions);
+        self
+    }
+
+    pub fn contexts(mut self, contexts: Vec<InteractionContextType>) -> Self {
+        self.contexts = Some(contexts);
+        self
+    }
+
+    pub fn integration_types(mut self, integration_types: Vec<ApplicationIntegrationType>) -> Self {
+        self.integration_types = Some(integration_types);
+        self
+    }
+
+    pub fn nsfw(mut self, nsfw: bool) -> Self {
+        self.nsfw = nsfw;
+        self
+    }
+}
+
+impl From<&str> for CommandGroup {
+    fn from(description: &str) -> Self {
+        CommandGroup::new(description)
+    }
+}
@@ -0,0 +1,78 @@
+use std::{pin::Pin, sync::Arc};
+
+use anyhow::Result;
+use twilight_model::{
+    application::interaction::Interaction, http::interaction::InteractionResponse,
+};
+
+use crate::checks::CommandMetadata;
+
+type LayerFuture = Pin<Box<dyn Future<Output = Result<InteractionResponse>> + Send>>;
+
+/// Middleware wrapped around the execution of commands
+///
+/// Layers run in the order they were added, each deciding whether to call the next one and what
+/// to do with its response. The innermost layer runs the command's checks and handler, or its
+/// autocomplete handler. Errors returned by handlers pass through the layers before they are
+/// turned into an error message, or an empty list of autocomplete choices.
+pub trait Layer<S>: Send + Sync {
+    fn call(
+        &self,
+        interaction: Arc<Interaction>,
+        state: Arc<S>,
+        command: CommandMetadata,
+        next: Next<S>,
+    ) -> LayerFuture;
+}
+
+impl<S, F, Fut> Layer<S> for F
+where
+    F: Fn(Arc<Interaction>, Arc<S>, CommandMetadata, Next<S>) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<InteractionResponse>> + Send + 'static,
+{
+    fn call(
+        &self,
+        interaction: Arc<Interaction>,
+        state: Arc<S>,
+        command: CommandMetadata,
+        next: Next<S>,
+    ) -> LayerFuture {
+        Box::pin(self(interaction, state, command, next))
+    }
+}
+
+/// The layers inside of the current one, followed by the command itself
+pub struct Next<S> {
+    /// The remaining layers, innermost first
+    layers: Vec<Arc<dyn Layer<S>>>,
+    command: CommandMetadata,
+    endpoint: Box<dyn FnOnce(Arc<Interaction>, Arc<S>) -> LayerFuture + Send>,
+}
+
+impl<S> Next<S> {
+    pub(crate) fn new<E>(
+        layers: &[Arc<dyn Layer<S>>],
+        command: CommandMetadata,
+        endpoint: E,
+    ) -> Self
+    where
+        E: FnOnce(Arc<Interaction>, Arc<S>) -> LayerFuture + Send + 'static,
+    {
+        Next {
+            layers: layers.iter().rev().cloned().collect(),
+            command,
+            endpoint: Box::new(endpoint),
+        }
+    }
+
+    /// Runs the next layer, or the command if there are no layers left
+    pub fn run(mut self, interaction: Arc<Interaction>, state: Arc<S>) -> LayerFuture {
+        match self.layers.pop() {
+            Some(layer) => {
+                let command = self.command.clone();
+                layer.call(interaction, state, command, self)
+            }
+            None => (self.endpoint)(interaction, state),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::arguments::CommandOption;
+
+/// Which string of a command, option or choice is being localized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalizedField {
+    Name,
+    Description,
+}
+
+/// Identifies a string to look up in a localization catalog
+///
+/// Formats as `<command>[.<option>[.<choice>]].<name|description>`, eg.
+/// `admin users ban.reason.description`.
+#[derive(Debug, Clone, Copy)]
+pub struct LocalizationKey<'a> {
+    /// The full path of the command, eg. `admin users ban`
+    pub command: &'a str,
+    /// The name of the option, if the string belongs to an option or choice
+    pub option: Option<&'a str>,
+    /// The name of the choice, if the string belongs to a choice
+    pub choice: Option<&'a str>,
+    /// The string being localized
+    pub field: LocalizedField,
+}
+
+impl Display for LocalizationKey<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.command)?;
+        if let Some(option) = self.option {
+            write!(f, ".{}", option)?;
+        }
+        if let Some(choice) = self.choice {
+            write!(f, ".{}", choice)?;
+        }
+        match self.field {
+            LocalizedField::Name => write!(f, ".name"),
+            LocalizedField::Description => write!(f, ".description"),
+        }
+    }
+}
+
+/// An external source of localizations, returning a map of locale to localized string
+pub(crate) type Catalog =
+    Box<dyn Fn(&LocalizationKey<'_>) -> Option<HashMap<String, String>> + Send + Sync>;
+
+/// Merges the localizations from the catalog with explicitly set ones, which take precedence
+pub(crate) fn localizations(
+    catalog: Option<&Catalog>,
+    key: &LocalizationKey<'_>,
+    explicit: Option<&HashMap<String, String>>,
+) -> Option<HashMap<String, String>> {
+    let mut merged = catalog.and_then(|catalog| catalog(key)).unwrap_or_default();
+    if let Some(explicit) = explicit {
+        merged.extend(explicit.clone());
+    }
+    (!merged.is_empty()).then_some(merged)
+}
+
+/// Fills in the localizations of an option and its choices from the catalog
+///
+/// Subcommands and subcommand groups are looked up by their full path, like commands registered
+/// under that path would be.
+pub(crate) fn localize_option(
+    catalog: Option<&Catalog>,
+    command: &str,
+    mut option: CommandOption,
+) -> CommandOption {
+    let name = option.name.clone().unwrap_or_default();
+    if let Some(options) = option.options.take() {
+        let path = format!("{command} {name}");
+        let key = |field| LocalizationKey {
+            command: &path,
+            option: None,
+            choice: None,
+            field,
+        };
+
+        option.name_localizations = localizations(
+            catalog,
+            &key(LocalizedField::Name),
+            option.name_localizations.as_ref(),
+        );
+        option.description_localizations = localizations(
+            catalog,
+            &key(LocalizedField::Description),
+            option.description_localizations.as_ref(),
+        );
+        option.options = Some(
+            options
+                .into_iter()
+                .map(|option| localize_option(catalog, &path, option))
+                .collect(),
+        );
+        return option;
+    }
+
+    let key = |choice, field| LocalizationKey {
+        command,
+        option: Some(&name),
+        choice,
+        field,
+    };
+
+    option.name_localizations = localizations(
+        catalog,
+        &key(None, LocalizedField::Name),
+        option.name_localizations.as_ref(),
+    );
+    option.description_localizations = localizations(
+        catalog,
+        &key(None, LocalizedField::Description),
+        option.description_localizations.as_ref(),
+    );
+    for choice in option.choices.iter_mut().flatten() {
+        choice.name_localizations = localizations(
+            catalog,
+            &key(Some(&choice.name), LocalizedField::Name),
+            choice.name_localizations.as_ref(),
+        );
+    }
+
+    option
+}
@@ -1,5 +1,16 @@
+mod autocomplete;
 mod context;
+mod group;
+mod layer;
+mod localization;
+mod permissions;
 mod slash;
 
-pub use crate::executor::context::ContextCommands;
-pub use crate::executor::slash::CommandExecutor as SlashCommands;
+pub use crate::executor::autocomplete::{
+    Autocomplete, AutocompleteRequest, MAX_AUTOCOMPLETE_CHOICES,
+};
+pub use crate::executor::context::{ContextCommand, ContextCommands, TargetError};
+pub use crate::executor::group::CommandGroup;
+pub use crate::executor::layer::{Layer, Next};
+pub use crate::executor::localization::{LocalizationKey, LocalizedField};
+pub use crate::executor::slash::{CommandExecutor as SlashCommands, ExecuteError};
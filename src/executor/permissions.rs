@@ -0,0 +1,30 @@
+use twilight_model::guild::Permissions;
+
+/// The permissions in `required` that are not in `granted`
+///
+/// `ADMINISTRATOR` grants every permission. Interactions without granted permissions, such as
+/// ones outside of a guild, are missing all of them.
+pub(crate) fn missing(required: Permissions, granted: Option<Permissions>) -> Permissions {
+    match granted {
+        Some(granted) if granted.contains(Permissions::ADMINISTRATOR) => Permissions::empty(),
+        Some(granted) => required - granted,
+        None => required,
+    }
+}
+
+/// Formats permissions for display, eg. `Manage Messages, Ban Members`
+pub(crate) fn display(permissions: Permissions) -> String {
+    permissions
+        .iter_names()
+        .map(|(name, _)| {
+            name.split('_')
+                .map(|word| {
+                    let (first, rest) = word.split_at(1);
+                    format!("{first}{}", rest.to_lowercase())
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -3,27 +3,51 @@ use std::{collections::HashMap, fmt::Debug, marker::PhantomData, pin::Pin, sync:
 use anyhow::Result;
 use twilight_model::{
     application::{
-        command::Command,
+        command::{Command, CommandOption, CommandOptionChoice},
         interaction::{
-            Interaction, InteractionContextType, application_command::CommandDataOption,
+            Interaction, InteractionContextType, InteractionData, InteractionType,
+            application_command::{CommandDataOption, CommandOptionValue},
         },
     },
     channel::message::MessageFlags,
+    guild::Permissions,
     http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    oauth::ApplicationIntegrationType,
 };
 use twilight_util::builder::{
     command::{CommandBuilder, SubCommandBuilder, SubCommandGroupBuilder},
     message::{ContainerBuilder, TextDisplayBuilder},
 };
 
+use crate::{
+    checks::{self, Check, CommandMetadata},
+    executor::{
+        autocomplete::{Autocomplete, AutocompleteRequest, MAX_AUTOCOMPLETE_CHOICES},
+        group::CommandGroup,
+        layer::{Layer, Next},
+        localization::{self, Catalog, LocalizationKey, LocalizedField},
+        permissions,
+    },
+};
+
 type CommandResponse = Result<InteractionResponse>;
 
+/// The reason an interaction could not be dispatched to a slash command
+#[derive(Debug, thiserror::Error)]
+pub enum ExecuteError {
+    #[error("Interaction is not a slash command")]
+    NotSlashCommand,
+    #[error("Unknown command `{0}`")]
+    UnknownCommand(String),
+}
+
 trait AsyncHandler<S>: Send + Sync {
     fn handle(
         &self,
         interaction: Arc<Interaction>,
         interaction_data: Vec<CommandDataOption>,
         state: Arc<S>,
+        command: CommandMetadata,
     ) -> Pin<Box<dyn Future<Output = CommandResponse> + Send>>;
 }
 
@@ -34,13 +58,14 @@ where
     Fut: Future<Output = CommandResponse> + Send + 'static,
     S: Send + Sync + 'static,
 {
-    handler: F,
+    handler: Arc<F>,
     _phantom: PhantomData<(C, S)>,
 }
 
-impl<C: crate::commands::Command, S, F, Fut> AsyncHandler<S> for TypedAsyncHandler<C, S, F, Fut>
+impl<C, S, F, Fut> AsyncHandler<S> for TypedAsyncHandler<C, S, F, Fut>
 where
-    F: Fn(C, Arc<Interaction>, Arc<S>) -> Fut + Send + Sync,
+    C: crate::commands::CommandChecks<S>,
+    F: Fn(C, Arc<Interaction>, Arc<S>) -> Fut + Send + Sync + 'static,
     Fut: Future<Output = CommandResponse> + Send + 'static,
     S: Send + Sync + 'static,
 {
@@ -49,33 +74,96 @@ where
         interaction: Arc<Interaction>,
         interaction_data: Vec<CommandDataOption>,
         state: Arc<S>,
+        command: CommandMetadata,
     ) -> Pin<Box<dyn Future<Output = CommandResponse> + Send>> {
-        let command_data = C::from_command_data(interaction_data);
+        let command_data = C::from_command_data(
+            interaction_data,
+            &crate::arguments::ParseContext::from_interaction(&interaction),
+        );
         let command_data = match command_data {
             Ok(data) => data,
-            Err(_) => {
-                return Box::pin(async {
-                    Ok(InteractionResponse {
-                        kind: twilight_model::http::interaction::InteractionResponseType::ChannelMessageWithSource,
-                        data: Some(InteractionResponseData {
-                            content: Some("Failed to parse command data.".to_string()),
-                            flags: Some(MessageFlags::EPHEMERAL),
-                            ..Default::default()
-                        }),
-                    })
-                });
+            Err(e) => {
+                return ephemeral_message(format!("Failed to parse command data: {e}"));
             }
         };
 
-        let fut = (self.handler)(command_data, Arc::clone(&interaction), state);
-        Box::pin(fut)
+        let missing = permissions::missing(
+            command_data.required_permissions(),
+            interaction
+                .member
+                .as_ref()
+                .and_then(|member| member.permissions),
+        );
+        if !missing.is_empty() {
+            return ephemeral_message(format!(
+                "You need the following permissions to use this command: {}",
+                permissions::display(missing)
+            ));
+        }
+
+        let missing = permissions::missing(
+            command_data.required_bot_permissions(),
+            interaction.app_permissions,
+        );
+        if !missing.is_empty() {
+            return ephemeral_message(format!(
+                "I need the following permissions in this channel to run this command: {}",
+                permissions::display(missing)
+            ));
+        }
+
+        let command_checks = command_data.checks();
+        let handler = Arc::clone(&self.handler);
+        Box::pin(async move {
+            let command_checks = command_checks.iter().map(Box::as_ref).collect::<Vec<_>>();
+            let denied = checks::run(&command_checks, &interaction, &state, &command).await;
+            if let Some(reason) = denied {
+                return Ok(ephemeral_response(reason));
+            }
+            handler(command_data, interaction, state).await
+        })
+    }
+}
+
+/// Replies with a message only the invoking user can see
+fn ephemeral_message(content: String) -> Pin<Box<dyn Future<Output = CommandResponse> + Send>> {
+    Box::pin(async move { Ok(ephemeral_response(content)) })
+}
+
+/// Replies to an autocomplete interaction with at most 25 of the given choices
+fn autocomplete_response(mut choices: Vec<CommandOptionChoice>) -> InteractionResponse {
+    choices.truncate(MAX_AUTOCOMPLETE_CHOICES);
+    InteractionResponse {
+        kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+        data: Some(InteractionResponseData {
+            choices: Some(choices),
+            ..Default::default()
+        }),
+    }
+}
+
+/// A message only the invoking user can see
+pub(crate) fn ephemeral_response(content: String) -> InteractionResponse {
+    InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(InteractionResponseData {
+            content: Some(content),
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        }),
     }
 }
 
 struct CommandInfo<S> {
-    handler: Box<dyn AsyncHandler<S>>,
+    handler: Arc<dyn AsyncHandler<S>>,
     options: Vec<crate::arguments::CommandOption>,
     description: &'static str,
+    name_localizations: Option<HashMap<String, String>>,
+    description_localizations: Option<HashMap<String, String>>,
+    contexts: Option<Vec<InteractionContextType>>,
+    integration_types: Option<Vec<ApplicationIntegrationType>>,
+    default_member_permissions: Option<Permissions>,
+    nsfw: bool,
 }
 
 enum CommandTree<S>
@@ -114,6 +202,31 @@ where
         }
     }
 
+    /// Finds the command invoked as `name` with `options`, following subcommands and subcommand
+    /// groups until a registered command is reached
+    ///
+    /// Returns the full name of the command and its own options.
+    fn resolve(
+        &self,
+        name: &str,
+        mut options: Vec<CommandDataOption>,
+    ) -> Result<(String, Vec<CommandDataOption>), ExecuteError> {
+        let mut path = vec![name.to_string()];
+        while self.get(&path).is_none() {
+            let Ok([option]) = <[_; 1]>::try_from(options) else {
+                return Err(ExecuteError::UnknownCommand(path.join(" ")));
+            };
+            let (CommandOptionValue::SubCommand(nested)
+            | CommandOptionValue::SubCommandGroup(nested)) = option.value
+            else {
+                return Err(ExecuteError::UnknownCommand(path.join(" ")));
+            };
+            path.push(option.name);
+            options = nested;
+        }
+        Ok((path.join(" "), options))
+    }
+
     fn get(&self, path: &[String]) -> Option<&CommandInfo<S>> {
         match self {
             CommandTree::Node(children) => {
@@ -168,6 +281,12 @@ where
     S: Send + Sync + 'static,
 {
     commands: CommandTree<S>,
+    autocomplete: HashMap<(String, String), Arc<dyn Autocomplete<S>>>,
+    groups: HashMap<String, CommandGroup>,
+    checks: Vec<Arc<dyn Check<S>>>,
+    node_checks: Vec<(String, Arc<dyn Check<S>>)>,
+    layers: Vec<Arc<dyn Layer<S>>>,
+    catalog: Option<Catalog>,
 }
 
 impl<S> CommandExecutor<S>
@@ -177,27 +296,90 @@ where
     /// Register an async command handler
     pub fn register<C, F, Fut>(&mut self, handler: F)
     where
-        C: crate::commands::Command,
+        C: crate::commands::CommandChecks<S>,
         F: Fn(C, Arc<Interaction>, Arc<S>) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = CommandResponse> + Send + 'static,
     {
         let handler = TypedAsyncHandler {
-            handler,
+            handler: Arc::new(handler),
             _phantom: std::marker::PhantomData,
         };
 
         let name = C::name().to_string();
         let command_info = CommandInfo {
-            handler: Box::new(handler),
+            handler: Arc::new(handler),
             options: C::options(),
             description: C::description(),
+            name_localizations: C::name_localizations(),
+            description_localizations: C::description_localizations(),
+            contexts: C::contexts(),
+            integration_types: C::integration_types(),
+            default_member_permissions: C::default_member_permissions(),
+            nsfw: C::nsfw(),
         };
 
         let path = name.split(' ').map(String::from).collect::<Vec<_>>();
         self.commands.insert(&path, command_info);
     }
 
+    /// Register an autocomplete handler for an option of the command with the given name
+    ///
+    /// Options of subcommands are registered under the subcommand's full name, eg. `admin ban`.
+    ///
+    /// Global and path checks run before the handler, and no choices are sent if one denies.
+    /// The command's own checks and required permissions don't run, as its options are incomplete
+    /// while they are being filled in.
+    pub fn register_autocomplete<A>(&mut self, command: &str, option: &str, handler: A)
+    where
+        A: Autocomplete<S> + 'static,
+    {
+        self.autocomplete
+            .insert((command.to_string(), option.to_string()), Arc::new(handler));
+    }
+
+    /// Sets the description and other metadata of the parent command or subcommand group at
+    /// `path`, eg. `admin` or `admin users`
+    pub fn group(&mut self, path: &str, group: impl Into<CommandGroup>) {
+        self.groups.insert(path.to_string(), group.into());
+    }
+
+    /// Adds a check that must pass for any command to run
+    ///
+    /// Also run before autocomplete handlers.
+    pub fn check<C>(&mut self, check: C)
+    where
+        C: Check<S> + 'static,
+    {
+        self.checks.push(Arc::new(check));
+    }
+
+    /// Adds a check that must pass for the command or group at `path`, eg. `admin`, and every
+    /// command below it to run
+    ///
+    /// Also run before the autocomplete handlers of these commands.
+    pub fn check_path<C>(&mut self, path: &str, check: C)
+    where
+        C: Check<S> + 'static,
+    {
+        self.node_checks.push((path.to_string(), Arc::new(check)));
+    }
+
+    /// Wraps the execution of every command in a layer
+    ///
+    /// Layers run in the order they were added, the first one being the outermost, and before
+    /// checks. They also wrap autocomplete handlers, whose interactions have the
+    /// `ApplicationCommandAutocomplete` kind.
+    pub fn layer<L>(&mut self, layer: L)
+    where
+        L: Layer<S> + 'static,
+    {
+        self.layers.push(Arc::new(layer));
+    }
+
     /// Executes a command with the given name
+    ///
+    /// Autocomplete interactions are routed to the handler registered for the focused option. If
+    /// it fails, the error is passed through the layers and no choices are sent.
     pub async fn execute(
         &self,
         name: &str,
@@ -208,31 +390,188 @@ where
         let path = name.split(' ').map(String::from).collect::<Vec<_>>();
         let handler = self.commands.get(&path)?;
 
-        Some(
-            handler
-                .handler
-                .handle(interaction, options, state)
-                .await
-                .unwrap_or_else(|e| {
-                    let container = ContainerBuilder::new()
-                        .accent_color(Some(0xAA0000))
-                        .component(
-                            TextDisplayBuilder::new(format!("An error occurred: {}", e)).build(),
-                        )
-                        .build();
+        if interaction.kind == InteractionType::ApplicationCommandAutocomplete {
+            return Some(self.autocomplete(name, interaction, options, state).await);
+        }
 
-                    InteractionResponse {
-                        kind: InteractionResponseType::ChannelMessageWithSource,
-                        data: Some(InteractionResponseData {
-                            components: Some(vec![container.into()]),
-                            flags: Some(MessageFlags::EPHEMERAL | MessageFlags::IS_COMPONENTS_V2),
-                            ..Default::default()
-                        }),
-                    }
+        let command = CommandMetadata {
+            name: invoked_path(name, &options),
+            kind: twilight_model::application::command::CommandType::ChatInput,
+        };
+        let command_checks = self.path_checks(&command.name);
+
+        let handler = Arc::clone(&handler.handler);
+        let endpoint_command = command.clone();
+        let next = Next::new(&self.layers, command, move |interaction, state| {
+            Box::pin(async move {
+                let command_checks = command_checks.iter().map(Arc::as_ref).collect::<Vec<_>>();
+                let denied =
+                    checks::run(&command_checks, &interaction, &state, &endpoint_command).await;
+                if let Some(reason) = denied {
+                    return Ok(ephemeral_response(reason));
+                }
+                handler
+                    .handle(interaction, options, state, endpoint_command)
+                    .await
+            })
+        });
+
+        Some(next.run(interaction, state).await.unwrap_or_else(|e| {
+            let container = ContainerBuilder::new()
+                .accent_color(Some(0xAA0000))
+                .component(TextDisplayBuilder::new(format!("An error occurred: {}", e)).build())
+                .build();
+
+            InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(InteractionResponseData {
+                    components: Some(vec![container.into()]),
+                    flags: Some(MessageFlags::EPHEMERAL | MessageFlags::IS_COMPONENTS_V2),
+                    ..Default::default()
                 }),
+            }
+        }))
+    }
+
+    /// Executes the slash command or autocomplete interaction, finding the command by following
+    /// the subcommands and subcommand groups in its options
+    pub async fn execute_interaction(
+        &self,
+        interaction: Arc<Interaction>,
+        state: Arc<S>,
+    ) -> Result<InteractionResponse, ExecuteError> {
+        let Some(InteractionData::ApplicationCommand(data)) = &interaction.data else {
+            return Err(ExecuteError::NotSlashCommand);
+        };
+        if data.kind != twilight_model::application::command::CommandType::ChatInput {
+            return Err(ExecuteError::NotSlashCommand);
+        }
+
+        let (name, options) = self.commands.resolve(&data.name, data.options.clone())?;
+        self.execute(&name, interaction, options, state)
+            .await
+            .ok_or(ExecuteError::UnknownCommand(name))
+    }
+
+    async fn autocomplete(
+        &self,
+        name: &str,
+        interaction: Arc<Interaction>,
+        options: Vec<CommandDataOption>,
+        state: Arc<S>,
+    ) -> InteractionResponse {
+        let command = CommandMetadata {
+            name: invoked_path(name, &options),
+            kind: twilight_model::application::command::CommandType::ChatInput,
+        };
+        let command_checks = self.path_checks(&command.name);
+        let handler = AutocompleteRequest::from_options(options).and_then(|request| {
+            self.autocomplete
+                .get(&(command.name.clone(), request.option.clone()))
+                .map(|handler| (Arc::clone(handler), request))
+        });
+
+        let endpoint_command = command.clone();
+        let next = Next::new(&self.layers, command, move |interaction, state| {
+            Box::pin(async move {
+                // Autocomplete responses can't show a message, so denials send no choices
+                let command_checks = command_checks.iter().map(Arc::as_ref).collect::<Vec<_>>();
+                let denied =
+                    checks::run(&command_checks, &interaction, &state, &endpoint_command).await;
+                if denied.is_some() {
+                    return Ok(autocomplete_response(Vec::new()));
+                }
+
+                let choices = match handler {
+                    Some((handler, request)) => {
+                        handler.complete(request, interaction, state).await?
+                    }
+                    None => Vec::new(),
+                };
+                Ok(autocomplete_response(choices))
+            })
+        });
+        next.run(interaction, state)
+            .await
+            .unwrap_or_else(|_| autocomplete_response(Vec::new()))
+    }
+
+    /// The global checks and the checks of every group containing the command called `name`, from
+    /// the outermost to the innermost
+    ///
+    /// The command's own checks are run after these, once its options have been parsed.
+    fn path_checks(&self, name: &str) -> Vec<Arc<dyn Check<S>>> {
+        let mut node_checks = self
+            .node_checks
+            .iter()
+            .filter(|(path, _)| {
+                name == path
+                    || name
+                        .strip_prefix(path.as_str())
+                        .is_some_and(|rest| rest.starts_with(' '))
+            })
+            .collect::<Vec<_>>();
+        node_checks.sort_by_key(|(path, _)| path.len());
+        self.checks
+            .iter()
+            .chain(node_checks.into_iter().map(|(_, check)| check))
+            .cloned()
+            .collect()
+    }
+
+    /// Sets a catalog used to look up localizations for commands, options and choices
+    ///
+    /// Localizations set on the commands themselves take precedence over the catalog.
+    pub fn localize_with<F>(&mut self, catalog: F)
+    where
+        F: Fn(&LocalizationKey<'_>) -> Option<HashMap<String, String>> + Send + Sync + 'static,
+    {
+        self.catalog = Some(Box::new(catalog));
+    }
+
+    /// Looks up the localizations of the name or description of the command at `path`
+    fn localizations(
+        &self,
+        path: &str,
+        field: LocalizedField,
+        explicit: Option<&HashMap<String, String>>,
+    ) -> Option<HashMap<String, String>> {
+        localization::localizations(
+            self.catalog.as_ref(),
+            &LocalizationKey {
+                command: path,
+                option: None,
+                choice: None,
+                field,
+            },
+            explicit,
         )
     }
 
+    /// Builds the options of the command at `path`
+    fn build_options(&self, path: &str, info: &CommandInfo<S>) -> Vec<CommandOption> {
+        info.options
+            .iter()
+            .map(|option| {
+                localization::localize_option(self.catalog.as_ref(), path, option.clone()).into()
+            })
+            .collect()
+    }
+
+    /// Builds the command at `path` as a subcommand
+    fn build_subcommand(&self, path: &str, name: &str, info: &CommandInfo<S>) -> CommandOption {
+        let mut subcommand = SubCommandBuilder::new(name, info.description).build();
+        subcommand.options = Some(self.build_options(path, info));
+        subcommand.name_localizations =
+            self.localizations(path, LocalizedField::Name, info.name_localizations.as_ref());
+        subcommand.description_localizations = self.localizations(
+            path,
+            LocalizedField::Description,
+            info.description_localizations.as_ref(),
+        );
+        subcommand
+    }
+
     /// Realizes the command tree into a list of `Command`s for registration with Discord
     pub fn build_commands(&self) -> Vec<Command> {
         let mut commands: Vec<Command> = Vec::new();
@@ -249,70 +588,102 @@ where
                             info.description,
                             twilight_model::application::command::CommandType::ChatInput,
                         )
-                        .contexts(vec![
-                            InteractionContextType::Guild,
-                            InteractionContextType::BotDm,
-                            InteractionContextType::PrivateChannel,
-                        ]);
-                        for option in &info.options {
-                            command = command.option(option.clone());
-                        }
+                        .contexts(info.contexts.clone().unwrap_or_else(default_contexts))
+                        .build();
+                        command.integration_types = info.integration_types.clone();
+                        command.default_member_permissions = info.default_member_permissions;
+                        command.nsfw = info.nsfw.then_some(true);
+                        command.options = self.build_options(name, info);
+                        command.name_localizations = self.localizations(
+                            name,
+                            LocalizedField::Name,
+                            info.name_localizations.as_ref(),
+                        );
+                        command.description_localizations = self.localizations(
+                            name,
+                            LocalizedField::Description,
+                            info.description_localizations.as_ref(),
+                        );
                     }
                     CommandTree::Node(subcommand_or_group) => {
+                        let group = self.groups.get(name);
                         command = CommandBuilder::new(
                             name,
-                            "No description provided",
+                            group_description(group),
                             twilight_model::application::command::CommandType::ChatInput,
                         )
-                        .contexts(vec![
-                            InteractionContextType::Guild,
-                            InteractionContextType::BotDm,
-                            InteractionContextType::PrivateChannel,
-                        ]);
+                        .contexts(
+                            group
+                                .and_then(|group| group.contexts.clone())
+                                .unwrap_or_else(default_contexts),
+                        )
+                        .build();
+                        command.integration_types =
+                            group.and_then(|group| group.integration_types.clone());
+                        command.nsfw = group.and_then(|group| group.nsfw.then_some(true));
+                        command.default_member_permissions =
+                            group.and_then(|group| group.default_member_permissions);
+                        command.name_localizations = self.localizations(
+                            name,
+                            LocalizedField::Name,
+                            group.and_then(|group| group.name_localizations.as_ref()),
+                        );
+                        command.description_localizations = self.localizations(
+                            name,
+                            LocalizedField::Description,
+                            group.and_then(|group| group.description_localizations.as_ref()),
+                        );
                         for (grandchild_name, grandchild) in subcommand_or_group.iter() {
+                            let grandchild_path = format!("{} {}", name, grandchild_name);
                             match grandchild {
                                 CommandTree::Leaf(info) => {
                                     // This is a subcommand
-                                    let mut subcommand =
-                                        SubCommandBuilder::new(grandchild_name, info.description);
-                                    for option in &info.options {
-                                        subcommand = subcommand.option(option.clone());
-                                    }
-                                    command = command.option(subcommand.build());
+                                    command.options.push(self.build_subcommand(
+                                        &grandchild_path,
+                                        grandchild_name,
+                                        info,
+                                    ));
                                 }
-                                CommandTree::Node(_) => {
+                                CommandTree::Node(sub_subcommands) => {
                                     // This is a subcommand group
-                                    if let CommandTree::Node(sub_subcommands) = grandchild {
-                                        let subcommand_group = SubCommandGroupBuilder::new(
-                                            grandchild_name,
-                                            "No description provided",
+                                    let group = self.groups.get(&grandchild_path);
+                                    let mut subcommand_group = SubCommandGroupBuilder::new(
+                                        grandchild_name,
+                                        group_description(group),
+                                    )
+                                    .build();
+                                    subcommand_group.name_localizations = self.localizations(
+                                        &grandchild_path,
+                                        LocalizedField::Name,
+                                        group.and_then(|group| group.name_localizations.as_ref()),
+                                    );
+                                    subcommand_group.description_localizations = self
+                                        .localizations(
+                                            &grandchild_path,
+                                            LocalizedField::Description,
+                                            group.and_then(|group| {
+                                                group.description_localizations.as_ref()
+                                            }),
                                         );
-                                        let mut subcommands = Vec::new();
-
-                                        for (subchild_name, subchild) in sub_subcommands.iter() {
-                                            if let CommandTree::Leaf(info) = subchild {
-                                                let mut subcommand = SubCommandBuilder::new(
-                                                    subchild_name,
-                                                    info.description,
-                                                );
-                                                for option in &info.options {
-                                                    subcommand = subcommand.option(option.clone());
-                                                }
-                                                subcommands.push(subcommand);
-                                            }
+
+                                    let mut subcommands = Vec::new();
+                                    for (subchild_name, subchild) in sub_subcommands.iter() {
+                                        if let CommandTree::Leaf(info) = subchild {
+                                            subcommands.push(self.build_subcommand(
+                                                &format!("{} {}", grandchild_path, subchild_name),
+                                                subchild_name,
+                                                info,
+                                            ));
                                         }
-                                        command = command.option(
-                                            subcommand_group.subcommands(subcommands).build(),
-                                        );
-                                    } else {
-                                        panic!("Expected Node for subcommand group");
                                     }
+                                    subcommand_group.options = Some(subcommands);
+                                    command.options.push(subcommand_group);
                                 }
                             }
                         }
                     }
                 }
-                commands.push(command.build());
+                commands.push(command);
             }
 
             commands
@@ -322,6 +693,38 @@ where
     }
 }
 
+/// The contexts used for commands that don't restrict where they can be used
+fn default_contexts() -> Vec<InteractionContextType> {
+    vec![
+        InteractionContextType::Guild,
+        InteractionContextType::BotDm,
+        InteractionContextType::PrivateChannel,
+    ]
+}
+
+/// The description of a parent command or subcommand group
+fn group_description(group: Option<&CommandGroup>) -> String {
+    group
+        .map(|group| group.description.clone())
+        .unwrap_or_else(|| "No description provided".to_string())
+}
+
+/// Appends the names of the subcommands and subcommand groups invoked through `options` to the
+/// command name
+fn invoked_path(name: &str, options: &[CommandDataOption]) -> String {
+    let mut path = name.to_string();
+    let mut options = options;
+    while let [option] = options
+        && let CommandOptionValue::SubCommand(nested) | CommandOptionValue::SubCommandGroup(nested) =
+            &option.value
+    {
+        path.push(' ');
+        path.push_str(&option.name);
+        options = nested;
+    }
+    path
+}
+
 impl<S> From<&CommandExecutor<S>> for Vec<Command>
 where
     S: Send + Sync + 'static,
@@ -338,6 +741,127 @@ where
     fn default() -> Self {
         CommandExecutor {
             commands: CommandTree::new(),
+            autocomplete: HashMap::new(),
+            groups: HashMap::new(),
+            checks: Vec::new(),
+            node_checks: Vec::new(),
+            layers: Vec::new(),
+            catalog: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Noop;
+
+    impl AsyncHandler<()> for Noop {
+        fn handle(
+            &self,
+            _interaction: Arc<Interaction>,
+            _interaction_data: Vec<CommandDataOption>,
+            _state: Arc<()>,
+            _command: CommandMetadata,
+        ) -> Pin<Box<dyn Future<Output = CommandResponse> + Send>> {
+            unreachable!("handlers are not run in these tests")
+        }
+    }
+
+    fn option(name: &str, value: CommandOptionValue) -> CommandDataOption {
+        CommandDataOption {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    fn subcommand(name: &str, options: Vec<CommandDataOption>) -> CommandDataOption {
+        option(name, CommandOptionValue::SubCommand(options))
+    }
+
+    fn group(name: &str, options: Vec<CommandDataOption>) -> CommandDataOption {
+        option(name, CommandOptionValue::SubCommandGroup(options))
+    }
+
+    fn tree(names: &[&str]) -> CommandTree<()> {
+        let mut tree = CommandTree::new();
+        for name in names {
+            let path = name.split(' ').map(String::from).collect::<Vec<_>>();
+            tree.insert(
+                &path,
+                CommandInfo {
+                    handler: Arc::new(Noop),
+                    options: Vec::new(),
+                    description: "",
+                    name_localizations: None,
+                    description_localizations: None,
+                    contexts: None,
+                    integration_types: None,
+                    default_member_permissions: None,
+                    nsfw: false,
+                },
+            );
+        }
+        tree
+    }
+
+    #[test]
+    fn invoked_path_appends_subcommands() {
+        let value = option("user", CommandOptionValue::String("a".to_string()));
+        assert_eq!(invoked_path("ping", &[]), "ping");
+        assert_eq!(invoked_path("ping", std::slice::from_ref(&value)), "ping");
+        assert_eq!(
+            invoked_path("admin", &[subcommand("ban", vec![value.clone()])]),
+            "admin ban"
+        );
+        assert_eq!(
+            invoked_path("admin", &[group("users", vec![subcommand("list", vec![])])]),
+            "admin users list"
+        );
+    }
+
+    #[test]
+    fn resolve_follows_subcommands_to_registered_commands() {
+        let tree = tree(&["ping", "admin ban", "admin users list"]);
+        let value = option("user", CommandOptionValue::String("a".to_string()));
+
+        let (name, options) = tree.resolve("ping", vec![value.clone()]).unwrap();
+        assert_eq!(name, "ping");
+        assert_eq!(options.len(), 1);
+
+        let (name, options) = tree
+            .resolve("admin", vec![subcommand("ban", vec![value])])
+            .unwrap();
+        assert_eq!(name, "admin ban");
+        assert_eq!(options[0].name, "user");
+
+        let (name, options) = tree
+            .resolve(
+                "admin",
+                vec![group("users", vec![subcommand("list", vec![])])],
+            )
+            .unwrap();
+        assert_eq!(name, "admin users list");
+        assert!(options.is_empty());
+    }
+
+    #[test]
+    fn resolve_rejects_unknown_commands() {
+        let tree = tree(&["admin ban", "admin users list"]);
+
+        let result = tree.resolve("admin", vec![subcommand("kick", vec![])]);
+        assert!(matches!(result, Err(ExecuteError::UnknownCommand(name)) if name == "admin kick"));
+
+        let result = tree.resolve(
+            "admin",
+            vec![group("roles", vec![subcommand("list", vec![])])],
+        );
+        assert!(
+            matches!(result, Err(ExecuteError::UnknownCommand(name)) if name == "admin roles list")
+        );
+
+        let result = tree.resolve("admin", Vec::new());
+        assert!(matches!(result, Err(ExecuteError::UnknownCommand(name)) if name == "admin"));
+    }
+}
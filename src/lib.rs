@@ -1,4 +1,5 @@
 pub mod arguments;
+pub mod checks;
 pub mod commands;
 
 #[cfg(feature = "executor")]
@@ -1,17 +1,25 @@
+use darling::FromMeta;
 use darling::ast::Data;
+use darling::util::SpannedValue;
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::DeriveInput;
 use syn::Ident;
 use syn::parse_macro_input;
+use syn::punctuated::Punctuated;
+use syn::{Expr, ExprUnary, Lit, Token, UnOp};
 
 use darling::FromDeriveInput;
 use darling::FromVariant;
 
+use crate::localizations::{self, Localizations};
+use crate::validation;
+
 #[derive(FromDeriveInput)]
-#[darling(attributes(choice), supports(enum_unit))]
+#[darling(attributes(choice), forward_attrs(repr), supports(enum_unit))]
 struct ChoicesEnumReceiver {
     ident: Ident,
+    attrs: Vec<syn::Attribute>,
     data: Data<ChoiceVariant, ()>,
 }
 
@@ -19,16 +27,165 @@ struct ChoicesEnumReceiver {
 #[darling(attributes(choice))]
 struct ChoiceVariant {
     ident: Ident,
+    discriminant: Option<Expr>,
+    #[darling(default)]
+    name: Option<SpannedValue<String>>,
     #[darling(default)]
-    name: Option<String>,
+    value: Option<ChoiceValue>,
     #[darling(default)]
-    value: Option<String>,
+    name_localizations: Option<Localizations>,
+}
+
+/// The value of a choice, as sent by Discord
+#[derive(Debug, Clone)]
+enum ChoiceValue {
+    String(String),
+    Integer(i64),
+    Number(f64),
+}
+
+impl ChoiceValue {
+    fn as_i64(&self) -> Option<i64> {
+        match self {
+            ChoiceValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            ChoiceValue::String(_) => None,
+            ChoiceValue::Integer(value) => Some(*value as f64),
+            ChoiceValue::Number(value) => Some(*value),
+        }
+    }
+}
+
+impl std::fmt::Display for ChoiceValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChoiceValue::String(value) => write!(f, "{}", value),
+            ChoiceValue::Integer(value) => write!(f, "{}", value),
+            ChoiceValue::Number(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl FromMeta for ChoiceValue {
+    fn from_expr(expr: &Expr) -> darling::Result<Self> {
+        match expr {
+            Expr::Lit(lit) => Self::from_value(&lit.lit),
+            Expr::Unary(ExprUnary {
+                op: UnOp::Neg(_),
+                expr,
+                ..
+            }) => match Self::from_expr(expr)? {
+                ChoiceValue::Integer(value) => Ok(ChoiceValue::Integer(-value)),
+                ChoiceValue::Number(value) => Ok(ChoiceValue::Number(-value)),
+                ChoiceValue::String(_) => Err(darling::Error::unexpected_expr_type(expr)),
+            },
+            Expr::Group(group) => Self::from_expr(&group.expr),
+            _ => Err(darling::Error::unexpected_expr_type(expr)),
+        }
+        .map_err(|e| e.with_span(expr))
+    }
+
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        match value {
+            Lit::Str(value) => Ok(ChoiceValue::String(value.value())),
+            Lit::Int(value) => Ok(ChoiceValue::Integer(value.base10_parse()?)),
+            Lit::Float(value) => Ok(ChoiceValue::Number(value.base10_parse()?)),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
+}
+
+/// The option type the choices of an enum are sent as
+#[derive(Debug, PartialEq, Eq)]
+enum ChoiceKind {
+    String,
+    Integer,
+    Number,
 }
 
 impl ChoicesEnumReceiver {
     fn variants(&self) -> Vec<&ChoiceVariant> {
         self.data.as_ref().take_enum().expect("should be an enum")
     }
+
+    /// Whether the enum has an integer `#[repr]`, in which case its discriminants are used as
+    /// the choice values
+    fn has_integer_repr(&self) -> bool {
+        self.attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("repr"))
+            .filter_map(|attr| {
+                attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)
+                    .ok()
+            })
+            .flatten()
+            .any(|ident| {
+                matches!(
+                    ident.to_string().as_str(),
+                    "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize"
+                )
+            })
+    }
+}
+
+/// Resolves the value of each variant, using discriminants if `use_discriminants` is set
+fn variant_values(
+    variants: &[&ChoiceVariant],
+    use_discriminants: bool,
+) -> darling::Result<Vec<ChoiceValue>> {
+    let mut next_discriminant = 0;
+    variants
+        .iter()
+        .map(|variant| {
+            if use_discriminants {
+                if let Some(discriminant) = &variant.discriminant {
+                    match ChoiceValue::from_expr(discriminant)? {
+                        ChoiceValue::Integer(value) => next_discriminant = value,
+                        _ => {
+                            return Err(darling::Error::custom(
+                                "Discriminants must be integer literals",
+                            )
+                            .with_span(discriminant));
+                        }
+                    }
+                }
+                next_discriminant += 1;
+            }
+
+            Ok(match (&variant.value, use_discriminants) {
+                (Some(value), _) => value.clone(),
+                (None, true) => ChoiceValue::Integer(next_discriminant - 1),
+                (None, false) => ChoiceValue::String(variant.ident.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Determines the option type of a set of choice values
+fn choice_kind(values: &[ChoiceValue]) -> darling::Result<ChoiceKind> {
+    let strings = values
+        .iter()
+        .filter(|value| matches!(value, ChoiceValue::String(_)))
+        .count();
+    if strings == values.len() {
+        Ok(ChoiceKind::String)
+    } else if strings > 0 {
+        Err(darling::Error::custom(
+            "Choice values must either all be strings or all be numbers",
+        ))
+    } else if values
+        .iter()
+        .any(|value| matches!(value, ChoiceValue::Number(_)))
+    {
+        Ok(ChoiceKind::Number)
+    } else {
+        Ok(ChoiceKind::Integer)
+    }
 }
 
 pub fn derive(tokens: TokenStream) -> TokenStream {
@@ -40,20 +197,55 @@ pub fn derive(tokens: TokenStream) -> TokenStream {
     };
 
     let enum_name = &receiver.ident;
+    // Without an integer repr, discriminants are never used, so numeric choices need a value on
+    // every variant
+    let use_discriminants = receiver.has_integer_repr();
+    if !use_discriminants
+        && receiver.variants().iter().any(|variant| {
+            matches!(
+                variant.value,
+                Some(ChoiceValue::Integer(_) | ChoiceValue::Number(_))
+            )
+        })
+    {
+        let mut errors = darling::Error::accumulator();
+        for variant in receiver.variants() {
+            if variant.value.is_none() {
+                errors.push(
+                    darling::Error::custom(
+                        "Every variant needs a value when one has a numeric value, unless the enum has an integer `#[repr]`",
+                    )
+                    .with_span(&variant.ident),
+                );
+            }
+        }
+        if let Err(err) = errors.finish() {
+            return TokenStream::from(err.write_errors());
+        }
+    }
+    let values = match variant_values(&receiver.variants(), use_discriminants) {
+        Ok(values) => values,
+        Err(err) => return TokenStream::from(err.write_errors()),
+    };
+    let kind = match choice_kind(&values) {
+        Ok(kind) => kind,
+        Err(err) => return TokenStream::from(err.write_errors()),
+    };
+
     let variants = receiver
         .variants()
         .iter()
-        .map(|variant| {
+        .zip(values)
+        .map(|(variant, value)| {
             (
                 variant.ident.clone(),
                 variant
                     .name
-                    .clone()
-                    .unwrap_or_else(|| variant.ident.to_string()),
-                variant
-                    .value
-                    .clone()
+                    .as_ref()
+                    .map(|name| name.to_string())
                     .unwrap_or_else(|| variant.ident.to_string()),
+                value,
+                variant.name_localizations.clone(),
             )
         })
         .collect::<Vec<_>>();
@@ -65,10 +257,33 @@ pub fn derive(tokens: TokenStream) -> TokenStream {
         );
     }
 
+    for (variant, (_ident, name, value, _localizations)) in
+        receiver.variants().iter().zip(&variants)
+    {
+        let name_span = variant
+            .name
+            .as_ref()
+            .map(|name| name.span())
+            .unwrap_or_else(|| variant.ident.span());
+        if let Err(e) = validation::choice_name(name) {
+            return TokenStream::from(e.with_span(&name_span).write_errors());
+        }
+        if let Some(name_localizations) = &variant.name_localizations
+            && let Err(e) = localizations::validate(name_localizations, validation::choice_name)
+        {
+            return TokenStream::from(e.write_errors());
+        }
+        if let ChoiceValue::String(value) = value
+            && let Err(e) = validation::choice_value(value)
+        {
+            return TokenStream::from(e.with_span(&variant.ident).write_errors());
+        }
+    }
+
     // Assert that all variants have unique values
     let mut seen_values = std::collections::HashSet::new();
-    for (_ident, _name, value) in &variants {
-        if !seen_values.insert(value) {
+    for (_ident, _name, value, _localizations) in &variants {
+        if !seen_values.insert(value.to_string()) {
             return TokenStream::from(
                 darling::Error::custom(format!("Duplicate choice value found: {}", value))
                     .write_errors(),
@@ -76,28 +291,136 @@ pub fn derive(tokens: TokenStream) -> TokenStream {
         }
     }
 
-    let command_option_choices = variants.iter().map(|(_ident, name, value)| {
+    let command_option_choices = variants.iter().map(|(_ident, name, value, name_localizations)| {
+        let value = match (&kind, value) {
+            (ChoiceKind::String, value) => {
+                let value = value.to_string();
+                quote! {
+                    ::twilight_model::application::command::CommandOptionChoiceValue::String(#value.to_string())
+                }
+            }
+            (ChoiceKind::Integer, value) => {
+                let value = value.as_i64().expect("should be an integer");
+                quote! {
+                    ::twilight_model::application::command::CommandOptionChoiceValue::Integer(#value)
+                }
+            }
+            (ChoiceKind::Number, value) => {
+                let value = value.as_f64().expect("should be a number");
+                quote! {
+                    ::twilight_model::application::command::CommandOptionChoiceValue::Number(#value)
+                }
+            }
+        };
+        let name_localizations = match name_localizations {
+            Some(name_localizations) => {
+                let name_localizations = localizations::to_tokens(name_localizations);
+                quote! { Some(#name_localizations) }
+            }
+            None => quote! { None },
+        };
         quote! {
             ::twilight_model::application::command::CommandOptionChoice {
                 name: #name.to_string(),
-                value: ::twilight_model::application::command::CommandOptionChoiceValue::String(#value.to_string()),
-                name_localizations: None,
+                value: #value,
+                name_localizations: #name_localizations,
             }
         }
     });
 
-    let argument_converter_matches = variants.iter().map(|(ident, _name, value)| {
-        quote! {
-            #value => Ok(#enum_name::#ident)
+    // Users pick choices by name, so errors list the names rather than the values
+    let allowed_names = variants
+        .iter()
+        .map(|(_ident, name, _value, _localizations)| name);
+
+    let option_type = match kind {
+        ChoiceKind::String => quote! { String },
+        ChoiceKind::Integer => quote! { Integer },
+        ChoiceKind::Number => quote! { Number },
+    };
+
+    let invalid_choice = quote! {
+        Err(::anyhow::anyhow!(::twilight_commands::arguments::Error::invalid_choice(&[
+            #(#allowed_names),*
+        ])))
+    };
+    let wrong_kind = quote! {
+        Err(::anyhow::anyhow!(::twilight_commands::arguments::Error::wrong_kind(
+            ::twilight_model::application::command::CommandOptionType::#option_type,
+            data,
+        )))
+    };
+
+    let converter = match kind {
+        ChoiceKind::String => {
+            let matches = variants
+                .iter()
+                .map(|(ident, _name, value, _localizations)| {
+                    let value = value.to_string();
+                    quote! {
+                        #value => Ok(#enum_name::#ident)
+                    }
+                });
+            quote! {
+                if let ::twilight_model::application::interaction::application_command::CommandOptionValue::String(value) = data {
+                    match value.as_str() {
+                        #(#matches),*,
+                        _ => #invalid_choice
+                    }
+                } else {
+                    #wrong_kind
+                }
+            }
         }
-    });
+        ChoiceKind::Integer => {
+            let matches = variants
+                .iter()
+                .map(|(ident, _name, value, _localizations)| {
+                    let value = value.as_i64().expect("should be an integer");
+                    quote! {
+                        #value => Ok(#enum_name::#ident)
+                    }
+                });
+            quote! {
+                if let ::twilight_model::application::interaction::application_command::CommandOptionValue::Integer(value) = data {
+                    match *value {
+                        #(#matches),*,
+                        _ => #invalid_choice
+                    }
+                } else {
+                    #wrong_kind
+                }
+            }
+        }
+        ChoiceKind::Number => {
+            let checks = variants
+                .iter()
+                .map(|(ident, _name, value, _localizations)| {
+                    let value = value.as_f64().expect("should be a number");
+                    quote! {
+                        if value == #value {
+                            return Ok(#enum_name::#ident);
+                        }
+                    }
+                });
+            quote! {
+                let value = match data {
+                    ::twilight_model::application::interaction::application_command::CommandOptionValue::Number(value) => *value,
+                    ::twilight_model::application::interaction::application_command::CommandOptionValue::Integer(value) => *value as f64,
+                    _ => return #wrong_kind,
+                };
+                #(#checks)*
+                #invalid_choice
+            }
+        }
+    };
 
     quote! {
         #[automatically_derived]
         impl ::twilight_commands::arguments::ToOption for #enum_name {
             fn to_option() -> ::twilight_commands::arguments::CommandOption {
                 ::twilight_commands::arguments::CommandOption::new(
-                    ::twilight_model::application::command::CommandOptionType::String
+                    ::twilight_model::application::command::CommandOptionType::#option_type
                 ).choices(vec![
                     #(#command_option_choices),*
                 ])
@@ -106,15 +429,11 @@ pub fn derive(tokens: TokenStream) -> TokenStream {
 
         #[automatically_derived]
         impl ::twilight_commands::arguments::ArgumentConverter for #enum_name {
-            fn convert(data: &::twilight_model::application::interaction::application_command::CommandOptionValue) -> ::anyhow::Result<Self> {
-                if let ::twilight_model::application::interaction::application_command::CommandOptionValue::String(value) = data {
-                    match value.as_str() {
-                        #(#argument_converter_matches),*,
-                        _ => Err(::anyhow::anyhow!(::twilight_commands::arguments::Error::InvalidType))
-                    }
-                } else {
-                    Err(::anyhow::anyhow!(::twilight_commands::arguments::Error::InvalidType))
-                }
+            fn convert(
+                data: &::twilight_model::application::interaction::application_command::CommandOptionValue,
+                _context: &::twilight_commands::arguments::ParseContext<'_>,
+            ) -> ::anyhow::Result<Self> {
+                #converter
             }
         }
     }
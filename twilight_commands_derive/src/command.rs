@@ -1,38 +1,349 @@
 use anyhow::Result;
-use darling::FromField;
-use darling::util::PathList;
-use darling::{FromDeriveInput, ast::Data};
+use darling::util::{Flag, PathList, SpannedValue};
+use darling::{FromDeriveInput, FromVariant, ast::Data, ast::Fields};
+use darling::{FromField, FromMeta};
 use proc_macro::TokenStream;
 use proc_macro2::Ident;
-use quote::quote;
+use quote::{quote, quote_spanned};
 use syn::parse_macro_input;
-use syn::{AngleBracketedGenericArguments, GenericArgument, PathArguments, Type};
+use syn::spanned::Spanned;
+use syn::{
+    AngleBracketedGenericArguments, Expr, ExprLit, ExprUnary, GenericArgument, Lit, PathArguments,
+    Type, UnOp,
+};
 use thiserror::Error;
 
+use crate::localizations::{self, Localizations};
+use crate::validation;
+
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(command), supports(struct_named, struct_unit))]
+#[darling(
+    attributes(command),
+    forward_attrs(doc),
+    supports(struct_named, struct_unit, enum_newtype)
+)]
 struct CommandReceiver {
     ident: syn::Ident,
-    data: Data<(), OptionReceiver>,
-    name: String,
+    attrs: Vec<syn::Attribute>,
+    data: Data<SubcommandReceiver, OptionReceiver>,
+    name: SpannedValue<String>,
+    #[darling(default)]
+    description: Option<SpannedValue<String>>,
+    #[darling(default)]
+    name_localizations: Option<Localizations>,
+    #[darling(default)]
+    description_localizations: Option<Localizations>,
+    /// Move required options before optional ones instead of failing to compile
+    #[darling(default)]
+    reorder_options: bool,
+    /// Where the command can be used, eg. `[Guild, BotDm]`
+    #[darling(default)]
+    contexts: Option<SpannedValue<PathArray>>,
+    /// How the app must be installed for the command to be available, eg. `[GuildInstall]`
+    #[darling(default)]
+    integration_types: Option<SpannedValue<PathArray>>,
+    /// Permissions members need to see the command by default, eg. `"BAN_MEMBERS | KICK_MEMBERS"`
+    #[darling(default)]
+    default_member_permissions: Option<SpannedValue<PermissionFlags>>,
+    /// Mark the command as age-restricted
+    #[darling(default)]
+    nsfw: Flag,
+    /// Permissions the invoking member must have, checked before the handler runs
+    #[darling(default)]
+    require_permissions: Option<PermissionFlags>,
+    /// Permissions the bot must have in the channel, checked before the handler runs
     #[darling(default)]
-    description: Option<String>,
+    bot_permissions: Option<PermissionFlags>,
+    /// Checks that must pass before the handler runs, eg. `check = guild_only` or
+    /// `check = has_role(id)`
+    #[darling(multiple, rename = "check")]
+    checks: Vec<Expr>,
+    /// The executor state the checks are run with, if they don't work with any state
+    #[darling(default)]
+    state: Option<syn::Path>,
+}
+
+/// A variant of a command enum, holding a subcommand or a subcommand group
+#[derive(Debug, FromVariant)]
+struct SubcommandReceiver {
+    ident: syn::Ident,
+    fields: Fields<syn::Type>,
 }
 
 #[derive(Debug, FromField)]
-#[darling(attributes(option))]
+#[darling(attributes(option), forward_attrs(doc))]
 struct OptionReceiver {
     ident: Option<syn::Ident>,
     ty: syn::Type,
+    attrs: Vec<syn::Attribute>,
     /// Override the name of the command option
     #[darling(default)]
-    name: Option<String>,
+    name: Option<SpannedValue<String>>,
     /// Set the description of the command option
     #[darling(default)]
-    description: Option<String>,
+    description: Option<SpannedValue<String>>,
+    /// Localized names of the command option, keyed by locale
+    #[darling(default)]
+    name_localizations: Option<Localizations>,
+    /// Localized descriptions of the command option, keyed by locale
+    #[darling(default)]
+    description_localizations: Option<Localizations>,
     /// For channel options, restrict to specific channel types
     #[darling(default)]
     channel_types: Option<PathList>,
+    /// For numeric options, the minimum allowed value
+    #[darling(default)]
+    min: Option<NumericBound>,
+    /// For numeric options, the maximum allowed value
+    #[darling(default)]
+    max: Option<NumericBound>,
+    /// For string options, the minimum allowed length
+    #[darling(default)]
+    min_length: Option<u16>,
+    /// For string options, the maximum allowed length
+    #[darling(default)]
+    max_length: Option<u16>,
+    /// For attachment options, the allowed content types (eg. `image/png` or `image/*`)
+    #[darling(default)]
+    content_types: Option<Vec<syn::LitStr>>,
+    /// For attachment options, the maximum file size (eg. `"8MB"`)
+    #[darling(default)]
+    max_size: Option<ByteSize>,
+    /// Enable autocomplete for the option
+    #[darling(default)]
+    autocomplete: bool,
+    /// Make the option optional, using this value when it is not provided
+    #[darling(default)]
+    default: Option<DefaultValue>,
+}
+
+/// The value used for an option when it is not provided
+#[derive(Debug)]
+enum DefaultValue {
+    /// Use the type's `Default` implementation
+    Trait,
+    /// Use the given expression
+    Expr(Expr),
+}
+
+impl DefaultValue {
+    /// Renders the default value for display in the option's description
+    ///
+    /// Only literals are shown, other expressions aren't meaningful to users.
+    fn display(&self) -> Option<String> {
+        match self {
+            DefaultValue::Trait => None,
+            DefaultValue::Expr(expr) => display_literal(expr),
+        }
+    }
+}
+
+/// Renders a literal, or a negated numeric literal, as it would be written by a user
+fn display_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(ExprLit { lit, .. }) => match lit {
+            Lit::Str(value) => Some(value.value()),
+            Lit::Int(value) => Some(value.base10_digits().to_string()),
+            Lit::Float(value) => Some(value.base10_digits().to_string()),
+            Lit::Bool(value) => Some(value.value.to_string()),
+            _ => None,
+        },
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) if matches!(
+            expr.as_ref(),
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(_) | Lit::Float(_),
+                ..
+            })
+        ) =>
+        {
+            display_literal(expr).map(|value| format!("-{value}"))
+        }
+        _ => None,
+    }
+}
+
+impl FromMeta for DefaultValue {
+    fn from_word() -> darling::Result<Self> {
+        Ok(DefaultValue::Trait)
+    }
+
+    fn from_expr(expr: &Expr) -> darling::Result<Self> {
+        Ok(DefaultValue::Expr(expr.clone()))
+    }
+}
+
+impl OptionReceiver {
+    /// Whether the option must be provided by the user
+    fn is_required(&self) -> bool {
+        self.default.is_none() && !is_option(&self.ty)
+    }
+}
+
+/// A numeric literal used as the bound of a numeric option
+#[derive(Debug, Clone, Copy)]
+enum NumericBound {
+    Integer(i64),
+    Number(f64),
+}
+
+impl NumericBound {
+    fn as_f64(self) -> f64 {
+        match self {
+            NumericBound::Integer(value) => value as f64,
+            NumericBound::Number(value) => value,
+        }
+    }
+
+    fn to_tokens(self) -> proc_macro2::TokenStream {
+        match self {
+            NumericBound::Integer(value) => quote! {
+                ::twilight_model::application::command::CommandOptionValue::Integer(#value)
+            },
+            NumericBound::Number(value) => quote! {
+                ::twilight_model::application::command::CommandOptionValue::Number(#value)
+            },
+        }
+    }
+}
+
+impl FromMeta for NumericBound {
+    fn from_expr(expr: &Expr) -> darling::Result<Self> {
+        match expr {
+            Expr::Lit(lit) => Self::from_value(&lit.lit),
+            Expr::Unary(ExprUnary {
+                op: UnOp::Neg(_),
+                expr,
+                ..
+            }) => Self::from_expr(expr).map(|bound| match bound {
+                NumericBound::Integer(value) => NumericBound::Integer(-value),
+                NumericBound::Number(value) => NumericBound::Number(-value),
+            }),
+            Expr::Group(group) => Self::from_expr(&group.expr),
+            _ => Err(darling::Error::unexpected_expr_type(expr)),
+        }
+        .map_err(|e| e.with_span(expr))
+    }
+
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        match value {
+            Lit::Int(value) => Ok(NumericBound::Integer(value.base10_parse()?)),
+            Lit::Float(value) => Ok(NumericBound::Number(value.base10_parse()?)),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
+}
+
+/// A file size in bytes, given either as an integer or a string with a unit such as `"8MB"`
+///
+/// Units are binary, so `1KB` is 1024 bytes.
+#[derive(Debug, Clone, Copy)]
+struct ByteSize(u64);
+
+impl FromMeta for ByteSize {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        let value = value.trim();
+        let split = value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len());
+        let (number, unit) = value.split_at(split);
+        let number = number
+            .parse::<u64>()
+            .map_err(|_| darling::Error::unknown_value(value))?;
+        let multiplier = match unit.trim().to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "KB" | "KIB" => 1 << 10,
+            "MB" | "MIB" => 1 << 20,
+            "GB" | "GIB" => 1 << 30,
+            _ => return Err(darling::Error::unknown_value(unit)),
+        };
+        number
+            .checked_mul(multiplier)
+            .map(ByteSize)
+            .ok_or_else(|| darling::Error::custom("File size is too large"))
+    }
+
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        match value {
+            Lit::Str(value) => Self::from_string(&value.value()),
+            Lit::Int(value) => Ok(ByteSize(value.base10_parse()?)),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+        .map_err(|e| e.with_span(value))
+    }
+}
+
+/// A list of paths given as an array, eg. `[Guild, BotDm]`
+#[derive(Debug, Clone)]
+struct PathArray(Vec<syn::Path>);
+
+impl FromMeta for PathArray {
+    fn from_expr(expr: &Expr) -> darling::Result<Self> {
+        match expr {
+            Expr::Array(array) => array
+                .elems
+                .iter()
+                .map(|elem| match elem {
+                    Expr::Path(path) => Ok(path.path.clone()),
+                    _ => Err(darling::Error::unexpected_expr_type(elem).with_span(elem)),
+                })
+                .collect::<darling::Result<Vec<_>>>()
+                .map(PathArray),
+            Expr::Group(group) => Self::from_expr(&group.expr),
+            _ => Err(darling::Error::unexpected_expr_type(expr).with_span(expr)),
+        }
+    }
+}
+
+/// Permission flags given as a string, eg. `"BAN_MEMBERS | KICK_MEMBERS"`
+///
+/// The flags are checked against `Permissions` by the compiler, so unknown flags are reported at
+/// the attribute.
+#[derive(Debug, Clone)]
+struct PermissionFlags(Vec<Ident>);
+
+impl PermissionFlags {
+    fn to_tokens(&self) -> proc_macro2::TokenStream {
+        let flags = &self.0;
+        if flags.is_empty() {
+            return quote! { ::twilight_model::guild::Permissions::empty() };
+        }
+        quote! { #(::twilight_model::guild::Permissions::#flags)|* }
+    }
+}
+
+impl FromMeta for PermissionFlags {
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        let Lit::Str(flags) = value else {
+            return Err(darling::Error::unexpected_lit_type(value));
+        };
+        flags
+            .value()
+            .split('|')
+            .map(str::trim)
+            .filter(|flag| !flag.is_empty())
+            .map(|flag| {
+                let mut ident = syn::parse_str::<Ident>(flag)
+                    .map_err(|_| darling::Error::unknown_value(flag).with_span(flags))?;
+                ident.set_span(flags.span());
+                Ok(ident)
+            })
+            .collect::<darling::Result<Vec<_>>>()
+            .map(PermissionFlags)
+    }
+}
+
+/// The kind of value a field holds, as far as can be told from its type
+#[derive(Debug, PartialEq, Eq)]
+enum FieldKind {
+    String,
+    Integer,
+    Number,
+    Attachment,
+    Other,
 }
 
 pub fn derive(input: TokenStream) -> TokenStream {
@@ -42,13 +353,68 @@ pub fn derive(input: TokenStream) -> TokenStream {
         Err(e) => return e.write_errors().into(),
     };
 
+    if let Err(e) = validation::command_name(&receiver.name) {
+        return e.with_span(&receiver.name.span()).write_errors().into();
+    }
+    if let Err(e) = validate_localizations(
+        &receiver.name_localizations,
+        &receiver.description_localizations,
+    ) {
+        return e.write_errors().into();
+    }
+    if let Err(e) = validate_top_level(&receiver) {
+        return e.write_errors().into();
+    }
+    let description = match command_description(&receiver) {
+        Ok(description) => description,
+        Err(e) => return e.write_errors().into(),
+    };
+    let metadata = metadata_fns(&receiver);
+    let checks = checks_impl(&receiver);
+
+    if receiver.data.is_enum() {
+        return derive_subcommands(receiver, &description, metadata, checks).into();
+    }
+
     let fields = receiver
         .data
         .take_struct()
-        .expect("only structs are supported")
+        .expect("only structs and enums are supported")
         .fields;
-    let options = fields
-        .iter()
+    if fields.len() > validation::MAX_OPTIONS {
+        return darling::Error::custom(format!(
+            "Commands can have at most {} options",
+            validation::MAX_OPTIONS
+        ))
+        .with_span(&receiver.ident)
+        .write_errors()
+        .into();
+    }
+
+    // Discord rejects commands with required options after optional ones
+    if !receiver.reorder_options
+        && let Some(first_optional) = fields.iter().position(|field| !field.is_required())
+        && let Some(misplaced) = fields[first_optional..]
+            .iter()
+            .find(|field| field.is_required())
+    {
+        let span = misplaced
+            .ident
+            .as_ref()
+            .map(|ident| ident.span())
+            .unwrap_or_else(proc_macro2::Span::call_site);
+        return darling::Error::custom(
+            "Required options must come before optional ones; move this field up or add #[command(reorder_options)]",
+        )
+        .with_span(&span)
+        .write_errors()
+        .into();
+    }
+
+    let mut ordered_fields = fields.iter().collect::<Vec<_>>();
+    ordered_fields.sort_by_key(|field| !field.is_required());
+    let options = ordered_fields
+        .into_iter()
         .map(field_option)
         .collect::<Vec<proc_macro2::TokenStream>>();
 
@@ -60,19 +426,37 @@ pub fn derive(input: TokenStream) -> TokenStream {
     };
     let ident = receiver.ident;
 
-    let struct_fields = field_names.iter().map(|(name, field_ident)| {
-        quote! {
-            #field_ident: ::twilight_commands::arguments::parse(&options_map, #name)?
-        }
-    });
-
-    let description = if let Some(desc) = &receiver.description {
-        desc.as_str()
-    } else {
-        "No description provided"
-    };
+    let struct_fields = field_names
+        .iter()
+        .zip(fields.iter())
+        .map(|((name, field_ident), field)| match &field.default {
+            Some(DefaultValue::Trait) => {
+                let ty = &field.ty;
+                quote! {
+                    #field_ident: ::twilight_commands::arguments::parse::<Option<#ty>>(&options_map, #name, context)?
+                        .unwrap_or_default()
+                }
+            }
+            Some(DefaultValue::Expr(expr)) => {
+                let ty = &field.ty;
+                // String literals are converted so they can be used as defaults for `String` fields
+                let expr = match expr {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(_), ..
+                    }) => quote! { ::std::convert::Into::into(#expr) },
+                    _ => quote! { #expr },
+                };
+                quote! {
+                    #field_ident: ::twilight_commands::arguments::parse::<Option<#ty>>(&options_map, #name, context)?
+                        .unwrap_or_else(|| #expr)
+                }
+            }
+            None => quote! {
+                #field_ident: ::twilight_commands::arguments::parse(&options_map, #name, context)?
+            },
+        });
 
-    let command_name = &receiver.name;
+    let command_name = receiver.name.as_str();
     let option_map_ast = if fields.is_empty() {
         quote! {}
     } else {
@@ -81,6 +465,7 @@ pub fn derive(input: TokenStream) -> TokenStream {
                 .iter()
                 .map(|opt| (opt.name.clone(), opt.value.clone()))
                 .collect::<::std::collections::HashMap<_, _>>();
+            ::twilight_commands::arguments::validate(&options_map, &Self::options(), context)?;
         }
     };
 
@@ -102,57 +487,553 @@ pub fn derive(input: TokenStream) -> TokenStream {
                 #description
             }
 
-            fn from_command_data(options: Vec<::twilight_model::application::interaction::application_command::CommandDataOption>) -> anyhow::Result<Self> {
+            #metadata
+
+            fn from_command_data(
+                options: Vec<::twilight_model::application::interaction::application_command::CommandDataOption>,
+                context: &::twilight_commands::arguments::ParseContext<'_>,
+            ) -> anyhow::Result<Self> {
                 #option_map_ast
                 Ok(Self {
                     #(#struct_fields,)*
                 })
             }
         }
+
+        #checks
     }
     .into()
 }
 
+/// Derives `Command` for an enum whose variants each hold a subcommand or a subcommand group
+fn derive_subcommands(
+    receiver: CommandReceiver,
+    description: &str,
+    metadata: proc_macro2::TokenStream,
+    checks: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let variants = receiver
+        .data
+        .take_enum()
+        .expect("only enums have subcommands");
+    if variants.len() > validation::MAX_OPTIONS {
+        return darling::Error::custom(format!(
+            "Commands can have at most {} subcommands",
+            validation::MAX_OPTIONS
+        ))
+        .with_span(&receiver.ident)
+        .write_errors();
+    }
+    if receiver.reorder_options {
+        return darling::Error::custom("reorder_options can only be specified for structs")
+            .with_span(&receiver.ident)
+            .write_errors();
+    }
+
+    let ident = receiver.ident;
+    let command_name = receiver.name.as_str();
+    let (variant_idents, variant_types): (Vec<_>, Vec<_>) = variants
+        .iter()
+        .map(|variant| (&variant.ident, &variant.fields.fields[0]))
+        .unzip();
+    // Subcommands can require more permissions than the command as a whole
+    let permissions = |flags: Option<&PermissionFlags>| {
+        flags
+            .map(PermissionFlags::to_tokens)
+            .unwrap_or_else(|| quote! { ::twilight_model::guild::Permissions::empty() })
+    };
+    let required_permissions = permissions(receiver.require_permissions.as_ref());
+    let required_bot_permissions = permissions(receiver.bot_permissions.as_ref());
+
+    quote! {
+        #[automatically_derived]
+        impl ::twilight_commands::commands::Command for #ident {
+            fn options() -> Vec<::twilight_commands::arguments::CommandOption> {
+                vec![
+                    #(::twilight_commands::commands::subcommand_option::<#variant_types>()),*
+                ]
+            }
+
+            fn name() -> &'static str {
+                #command_name
+            }
+
+            fn description() -> &'static str {
+                #description
+            }
+
+            #metadata
+
+            fn required_permissions(&self) -> ::twilight_model::guild::Permissions {
+                #required_permissions
+                    | match self {
+                        #(Self::#variant_idents(command) => ::twilight_commands::commands::Command::required_permissions(command),)*
+                    }
+            }
+
+            fn required_bot_permissions(&self) -> ::twilight_model::guild::Permissions {
+                #required_bot_permissions
+                    | match self {
+                        #(Self::#variant_idents(command) => ::twilight_commands::commands::Command::required_bot_permissions(command),)*
+                    }
+            }
+
+            fn from_command_data(
+                options: Vec<::twilight_model::application::interaction::application_command::CommandDataOption>,
+                context: &::twilight_commands::arguments::ParseContext<'_>,
+            ) -> anyhow::Result<Self> {
+                let (name, options) = ::twilight_commands::commands::take_subcommand(#command_name, options)?;
+                #(
+                    if name == ::twilight_commands::commands::subcommand_name::<#variant_types>() {
+                        return Ok(Self::#variant_idents(
+                            <#variant_types as ::twilight_commands::commands::Command>::from_command_data(options, context)?
+                        ));
+                    }
+                )*
+                Err(::anyhow::anyhow!(
+                    ::twilight_commands::arguments::Error::UnknownSubcommand { option: name }
+                ))
+            }
+        }
+
+        #checks
+    }
+}
+
+/// Rejects attributes that Discord only supports on top-level commands when set on a subcommand,
+/// as they would be silently ignored
+fn validate_top_level(receiver: &CommandReceiver) -> darling::Result<()> {
+    let Some((parent, _)) = receiver.name.split_once(' ') else {
+        return Ok(());
+    };
+    let error = |attribute: &str, span: proc_macro2::Span| {
+        darling::Error::custom(format!(
+            "`{attribute}` can only be set on top-level commands, set it on the parent command \
+             with `executor.group(\"{parent}\", CommandGroup::new(..).{attribute}(..))` instead"
+        ))
+        .with_span(&span)
+    };
+
+    let mut errors = darling::Error::accumulator();
+    if let Some(contexts) = &receiver.contexts {
+        errors.push(error("contexts", contexts.span()));
+    }
+    if let Some(integration_types) = &receiver.integration_types {
+        errors.push(error("integration_types", integration_types.span()));
+    }
+    if let Some(permissions) = &receiver.default_member_permissions {
+        errors.push(error("default_member_permissions", permissions.span()));
+    }
+    if receiver.nsfw.is_present() {
+        errors.push(error("nsfw", receiver.nsfw.span()));
+    }
+    errors.finish()
+}
+
+/// Gets the description of a command from its attribute or doc comments
+fn command_description(receiver: &CommandReceiver) -> darling::Result<String> {
+    if let Some(description) = &receiver.description {
+        validation::description(description).map_err(|e| e.with_span(&description.span()))?;
+        return Ok(description.to_string());
+    }
+    Ok(doc_description(&receiver.attrs)?.unwrap_or_else(|| "No description provided".to_string()))
+}
+
+/// Checks localized names and descriptions against the same rules as the unlocalized ones
+fn validate_localizations(
+    name_localizations: &Option<Localizations>,
+    description_localizations: &Option<Localizations>,
+) -> darling::Result<()> {
+    if let Some(name_localizations) = name_localizations {
+        localizations::validate(name_localizations, validation::name)?;
+    }
+    if let Some(description_localizations) = description_localizations {
+        localizations::validate(description_localizations, validation::description)?;
+    }
+    Ok(())
+}
+
+/// Generates the `Command` methods returning the localizations and other metadata of a command
+fn metadata_fns(receiver: &CommandReceiver) -> proc_macro2::TokenStream {
+    let name_localizations = receiver
+        .name_localizations
+        .as_ref()
+        .map(|name_localizations| {
+            let name_localizations = localizations::to_tokens(name_localizations);
+            quote! {
+                fn name_localizations() -> Option<::std::collections::HashMap<String, String>> {
+                    Some(#name_localizations)
+                }
+            }
+        });
+    let description_localizations =
+        receiver
+            .description_localizations
+            .as_ref()
+            .map(|description_localizations| {
+                let description_localizations = localizations::to_tokens(description_localizations);
+                quote! {
+                    fn description_localizations() -> Option<::std::collections::HashMap<String, String>> {
+                        Some(#description_localizations)
+                    }
+                }
+            });
+
+    let contexts = receiver.contexts.as_deref().map(|PathArray(contexts)| {
+        quote! {
+            fn contexts() -> Option<Vec<::twilight_model::application::interaction::InteractionContextType>> {
+                Some(vec![#(::twilight_model::application::interaction::InteractionContextType::#contexts),*])
+            }
+        }
+    });
+    let integration_types = receiver
+        .integration_types
+        .as_deref()
+        .map(|PathArray(integration_types)| {
+            quote! {
+                fn integration_types() -> Option<Vec<::twilight_model::oauth::ApplicationIntegrationType>> {
+                    Some(vec![#(::twilight_model::oauth::ApplicationIntegrationType::#integration_types),*])
+                }
+            }
+        });
+
+    let default_member_permissions = receiver.default_member_permissions.as_ref().map(
+        |permissions| {
+            let permissions = permissions.to_tokens();
+            quote! {
+                fn default_member_permissions() -> Option<::twilight_model::guild::Permissions> {
+                    Some(#permissions)
+                }
+            }
+        },
+    );
+    let required_permissions = receiver
+        .require_permissions
+        .as_ref()
+        .filter(|_| receiver.data.is_struct())
+        .map(|permissions| {
+            let permissions = permissions.to_tokens();
+            quote! {
+                fn required_permissions(&self) -> ::twilight_model::guild::Permissions {
+                    #permissions
+                }
+            }
+        });
+    let required_bot_permissions = receiver
+        .bot_permissions
+        .as_ref()
+        .filter(|_| receiver.data.is_struct())
+        .map(|permissions| {
+            let permissions = permissions.to_tokens();
+            quote! {
+                fn required_bot_permissions(&self) -> ::twilight_model::guild::Permissions {
+                    #permissions
+                }
+            }
+        });
+    let nsfw = receiver.nsfw.is_present().then(|| {
+        quote! {
+            fn nsfw() -> bool {
+                true
+            }
+        }
+    });
+
+    quote! {
+        #name_localizations
+        #description_localizations
+        #contexts
+        #integration_types
+        #default_member_permissions
+        #nsfw
+        #required_permissions
+        #required_bot_permissions
+    }
+}
+
+/// Generates the `CommandChecks` implementation of a command, for the state set with
+/// `#[command(state = ...)]` or for every state
+///
+/// The checks of command enums also include the checks of the invoked subcommand.
+fn checks_impl(receiver: &CommandReceiver) -> proc_macro2::TokenStream {
+    let ident = &receiver.ident;
+    let (generics, state) = match &receiver.state {
+        Some(state) => (quote! {}, quote! { #state }),
+        None => (quote! { <S> }, quote! { S }),
+    };
+    let state_bounds = receiver
+        .state
+        .is_none()
+        .then(|| quote! { S: Send + Sync + 'static, });
+    let (variant_idents, variant_types): (Vec<_>, Vec<_>) = match receiver.data.as_ref() {
+        Data::Enum(variants) => variants
+            .into_iter()
+            .map(|variant| (&variant.ident, &variant.fields.fields[0]))
+            .unzip(),
+        Data::Struct(_) => (Vec::new(), Vec::new()),
+    };
+
+    // Spanned at each check, so checks that don't work with the state are reported there
+    let checks = receiver
+        .checks
+        .iter()
+        .map(|check| {
+            quote_spanned! {check.span()=>
+                ::twilight_commands::checks::boxed(#check)
+            }
+        })
+        .collect::<Vec<_>>();
+    let checks_fn = if receiver.data.is_enum() {
+        Some(quote! {
+            fn checks(&self) -> Vec<Box<dyn ::twilight_commands::checks::Check<#state>>> {
+                let mut checks: Vec<Box<dyn ::twilight_commands::checks::Check<#state>>> = vec![
+                    #(#checks),*
+                ];
+                checks.extend(match self {
+                    #(Self::#variant_idents(command) => ::twilight_commands::commands::CommandChecks::<#state>::checks(command),)*
+                });
+                checks
+            }
+        })
+    } else {
+        (!checks.is_empty()).then(|| {
+            quote! {
+                fn checks(&self) -> Vec<Box<dyn ::twilight_commands::checks::Check<#state>>> {
+                    vec![#(#checks),*]
+                }
+            }
+        })
+    };
+
+    quote! {
+        #[automatically_derived]
+        impl #generics ::twilight_commands::commands::CommandChecks<#state> for #ident
+        where
+            #state_bounds
+            #(#variant_types: ::twilight_commands::commands::CommandChecks<#state>,)*
+        {
+            #checks_fn
+        }
+    }
+}
+
 fn field_option(field: &OptionReceiver) -> proc_macro2::TokenStream {
     // Assert that either field_name_override or field_name is Some
     let name = match get_name(field) {
         Ok(name) => name,
         Err(e) => return e.to_compile_error(),
     };
-    let default_description = "No description provided".to_string();
-    let description = field.description.as_ref().unwrap_or(&default_description);
+    let doc_description = match doc_description(&field.attrs) {
+        Ok(doc) => doc,
+        Err(e) => return e.write_errors(),
+    };
+    let mut description = field
+        .description
+        .as_deref()
+        .cloned()
+        .or(doc_description)
+        .unwrap_or_else(|| "No description provided".to_string());
+    if let Some(default) = field.default.as_ref().and_then(DefaultValue::display) {
+        description = format!("{description} (default: {default})");
+    }
     let ty = &field.ty;
 
+    let name_span = field
+        .name
+        .as_ref()
+        .map(|name| name.span())
+        .or_else(|| field.ident.as_ref().map(|ident| ident.span()))
+        .unwrap_or_else(proc_macro2::Span::call_site);
+    if let Err(e) = validation::name(&name) {
+        return e.with_span(&name_span).write_errors();
+    }
+    let description_span = field
+        .description
+        .as_ref()
+        .map(|description| description.span())
+        .unwrap_or(name_span);
+    if let Err(e) = validation::description(&description) {
+        return e.with_span(&description_span).write_errors();
+    }
+    if let Err(e) =
+        validate_localizations(&field.name_localizations, &field.description_localizations)
+    {
+        return e.write_errors();
+    }
+
     if field.channel_types.is_some() && !validate_channel_type(ty) {
         return darling::Error::custom(
-            "channel_types can only be specified for fields of type Id<ChannelMarker>",
+            "channel_types can only be specified for fields of type Id<ChannelMarker> or InteractionChannel",
         )
         .write_errors();
     }
 
+    if let Err(e) = validate_constraints(field) {
+        return e.write_errors();
+    }
+
     let ty = add_turbofish(ty);
 
-    let channel_types = field.channel_types.as_ref().map(|types| {
+    let mut option = quote! {
+        #ty::to_option().name(#name).description(#description)
+    };
+
+    if let Some(types) = &field.channel_types {
         let types = types
             .iter()
             .map(|path| quote! { ::twilight_model::channel::ChannelType::#path });
-        quote! {
-            vec![#(#types),*]
+        option = quote! { #option.channel_types(vec![#(#types),*]) };
+    }
+    if let Some(min) = field.min {
+        let min = min.to_tokens();
+        option = quote! { #option.min_value(#min) };
+    }
+    if let Some(max) = field.max {
+        let max = max.to_tokens();
+        option = quote! { #option.max_value(#max) };
+    }
+    if let Some(min_length) = field.min_length {
+        option = quote! { #option.min_length(#min_length) };
+    }
+    if let Some(max_length) = field.max_length {
+        option = quote! { #option.max_length(#max_length) };
+    }
+    if let Some(name_localizations) = &field.name_localizations {
+        let name_localizations = localizations::to_tokens(name_localizations);
+        option = quote! { #option.name_localizations(#name_localizations) };
+    }
+    if let Some(description_localizations) = &field.description_localizations {
+        let description_localizations = localizations::to_tokens(description_localizations);
+        option = quote! { #option.description_localizations(#description_localizations) };
+    }
+    if let Some(content_types) = &field.content_types {
+        option = quote! { #option.content_types(vec![#(#content_types.to_string()),*]) };
+    }
+    if let Some(ByteSize(max_size)) = field.max_size {
+        option = quote! { #option.max_size(#max_size) };
+    }
+    if field.autocomplete {
+        option = quote! { #option.autocomplete(true) };
+    }
+    if field.default.is_some() {
+        option = quote! { #option.required(false) };
+    }
+
+    option
+}
+
+/// Checks that the constraints on a field match its type
+fn validate_constraints(field: &OptionReceiver) -> darling::Result<()> {
+    let kind = field_kind(&field.ty);
+
+    if field.min.is_some() || field.max.is_some() {
+        if kind != FieldKind::Integer && kind != FieldKind::Number {
+            return Err(darling::Error::custom(
+                "min and max can only be specified for numeric fields",
+            )
+            .with_span(&field.ty));
         }
-    });
+        if kind == FieldKind::Integer
+            && [field.min, field.max]
+                .iter()
+                .any(|bound| matches!(bound, Some(NumericBound::Number(_))))
+        {
+            return Err(
+                darling::Error::custom("min and max must be integers for integer fields")
+                    .with_span(&field.ty),
+            );
+        }
+        if let (Some(min), Some(max)) = (field.min, field.max)
+            && min.as_f64() > max.as_f64()
+        {
+            return Err(
+                darling::Error::custom("min must not be greater than max").with_span(&field.ty)
+            );
+        }
+    }
 
-    match channel_types {
-        Some(channel_types) => {
-            quote! {
-                #ty::to_option().name(#name).description(#description).channel_types(#channel_types)
-            }
+    if field.min_length.is_some() || field.max_length.is_some() {
+        if kind != FieldKind::String {
+            return Err(darling::Error::custom(
+                "min_length and max_length can only be specified for String fields",
+            )
+            .with_span(&field.ty));
         }
-        None => {
-            quote! {
-                #ty::to_option().name(#name).description(#description)
-            }
+        if let (Some(min), Some(max)) = (field.min_length, field.max_length)
+            && min > max
+        {
+            return Err(
+                darling::Error::custom("min_length must not be greater than max_length")
+                    .with_span(&field.ty),
+            );
         }
     }
+
+    if (field.content_types.is_some() || field.max_size.is_some()) && kind != FieldKind::Attachment
+    {
+        return Err(darling::Error::custom(
+            "content_types and max_size can only be specified for Attachment fields",
+        )
+        .with_span(&field.ty));
+    }
+
+    if field.default.is_some() && is_option(&field.ty) {
+        return Err(
+            darling::Error::custom("default cannot be specified for Option fields")
+                .with_span(&field.ty),
+        );
+    }
+
+    if field.autocomplete
+        && !matches!(
+            kind,
+            FieldKind::String | FieldKind::Integer | FieldKind::Number
+        )
+    {
+        return Err(darling::Error::custom(
+            "autocomplete can only be specified for String and numeric fields",
+        )
+        .with_span(&field.ty));
+    }
+
+    Ok(())
+}
+
+/// Builds a description from `///` doc comments, trimming each line and joining them with spaces
+fn doc_description(attrs: &[syn::Attribute]) -> darling::Result<Option<String>> {
+    let docs = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .collect::<Vec<_>>();
+
+    let lines = docs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(doc), ..
+                    }),
+                ..
+            }) => Some(doc.value()),
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        return Ok(None);
+    }
+
+    let description = lines.join(" ");
+    validation::description(&description).map_err(|e| e.with_span(docs[0]))?;
+    Ok(Some(description))
 }
 
 #[derive(Error, Debug)]
@@ -182,7 +1063,7 @@ enum GetNameError {
 /// Gets the name of an `OptionReceiver`
 fn get_name(field: &OptionReceiver) -> Result<String, GetNameError> {
     if let Some(name) = &field.name {
-        Ok(name.clone())
+        Ok(name.to_string())
     } else if let Some(ident) = &field.ident {
         Ok(ident.to_string())
     } else {
@@ -235,10 +1116,60 @@ fn transform_generic_arguments(args: &AngleBracketedGenericArguments) -> proc_ma
     quote! { <#(#args),*> }
 }
 
+/// Gets the inner type of an `Option<T>`, or `None` if the type is not an `Option`
+fn option_inner_type(type_: &Type) -> Option<&Type> {
+    if let Type::Path(type_path) = type_
+        && let Some(segment) = type_path.path.segments.last()
+        && segment.ident == "Option"
+        && let PathArguments::AngleBracketed(args) = &segment.arguments
+        && let Some(GenericArgument::Type(inner)) = args.args.first()
+    {
+        Some(inner)
+    } else {
+        None
+    }
+}
+
+fn is_option(type_: &Type) -> bool {
+    option_inner_type(type_).is_some()
+}
+
+/// Determines the kind of value a field holds, looking through `Option<T>`
+fn field_kind(type_: &Type) -> FieldKind {
+    if let Some(inner) = option_inner_type(type_) {
+        return field_kind(inner);
+    }
+
+    let Type::Path(type_path) = type_ else {
+        return FieldKind::Other;
+    };
+    let Some(segment) = type_path.path.segments.last() else {
+        return FieldKind::Other;
+    };
+
+    match segment.ident.to_string().as_str() {
+        "String" => FieldKind::String,
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128"
+        | "usize" => FieldKind::Integer,
+        "f32" | "f64" => FieldKind::Number,
+        "Attachment" => FieldKind::Attachment,
+        _ => FieldKind::Other,
+    }
+}
+
 fn validate_channel_type(type_: &Type) -> bool {
+    if let Some(inner) = option_inner_type(type_) {
+        return validate_channel_type(inner);
+    }
+
     match type_ {
         Type::Path(type_path) => {
             let path = &type_path.path;
+            if let Some(segment) = path.segments.last()
+                && segment.ident == "InteractionChannel"
+            {
+                return true;
+            }
             if let Some(segment) = path.segments.last()
                 && segment.ident == "Id"
                 && let PathArguments::AngleBracketed(args) = &segment.arguments
@@ -258,3 +1189,26 @@ impl GetNameError {
         darling::Error::custom(message).write_errors()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_size_parses_units() {
+        let size = |value: &str| ByteSize::from_string(value).map(|ByteSize(size)| size);
+        assert_eq!(size("512").unwrap(), 512);
+        assert_eq!(size("8B").unwrap(), 8);
+        assert_eq!(size("8MB").unwrap(), 8 << 20);
+        assert_eq!(size("8 MiB").unwrap(), 8 << 20);
+        assert_eq!(size("1kb").unwrap(), 1 << 10);
+        assert_eq!(size("2 GiB").unwrap(), 2 << 30);
+    }
+
+    #[test]
+    fn byte_size_rejects_invalid_sizes() {
+        assert!(ByteSize::from_string("8 TB").is_err());
+        assert!(ByteSize::from_string("MB").is_err());
+        assert!(ByteSize::from_string("18446744073709551615 GB").is_err());
+    }
+}
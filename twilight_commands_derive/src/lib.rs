@@ -2,6 +2,8 @@ use proc_macro::TokenStream;
 
 mod choices;
 mod command;
+mod localizations;
+mod validation;
 
 #[proc_macro_derive(Command, attributes(option, command))]
 pub fn command_derive(input: TokenStream) -> TokenStream {
@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+
+use darling::util::SpannedValue;
+use quote::quote;
+
+/// Localized strings keyed by locale, as given in a `name_localizations(...)` or
+/// `description_localizations(...)` attribute
+///
+/// Locales are written as identifiers, so underscores are converted to dashes (`en_US` becomes
+/// `en-US`).
+pub type Localizations = SpannedValue<HashMap<String, String>>;
+
+/// Checks every localized string with `validate`, reporting errors at the attribute
+pub fn validate(
+    localizations: &Localizations,
+    validate: impl Fn(&str) -> darling::Result<()>,
+) -> darling::Result<()> {
+    localizations
+        .values()
+        .try_for_each(|value| validate(value))
+        .map_err(|e| e.with_span(&localizations.span()))
+}
+
+/// Generates an expression building a `HashMap` of the given localizations
+pub fn to_tokens(localizations: &Localizations) -> proc_macro2::TokenStream {
+    let mut entries = localizations
+        .iter()
+        .map(|(locale, value)| (locale.replace('_', "-"), value))
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    let entries = entries.iter().map(|(locale, value)| {
+        quote! { (#locale.to_string(), #value.to_string()) }
+    });
+    quote! {
+        ::std::collections::HashMap::from([#(#entries),*])
+    }
+}
@@ -0,0 +1,84 @@
+/// The maximum length of a command, option or choice name
+pub const MAX_NAME_LENGTH: usize = 32;
+/// The maximum length of a command or option description
+pub const MAX_DESCRIPTION_LENGTH: usize = 100;
+/// The maximum number of options a command may have
+pub const MAX_OPTIONS: usize = 25;
+/// The maximum length of a choice name or string value
+pub const MAX_CHOICE_LENGTH: usize = 100;
+/// The maximum number of segments in a command name (`command group subcommand`)
+pub const MAX_COMMAND_DEPTH: usize = 3;
+
+/// Checks that a command or option name matches `^[-_\p{L}\p{N}]{1,32}$` and is lowercase
+pub fn name(name: &str) -> darling::Result<()> {
+    let length = name.chars().count();
+    if length == 0 || length > MAX_NAME_LENGTH {
+        return Err(darling::Error::custom(format!(
+            "Name `{}` must be between 1 and {} characters long",
+            name, MAX_NAME_LENGTH
+        )));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_alphanumeric() || *c == '-' || *c == '_'))
+    {
+        return Err(darling::Error::custom(format!(
+            "Name `{}` contains invalid character `{}`; only letters, numbers, `-` and `_` are allowed",
+            name, c
+        )));
+    }
+    if name.chars().any(char::is_uppercase) {
+        return Err(darling::Error::custom(format!(
+            "Name `{}` must be lowercase",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that a space-separated command path has valid segments and is at most three deep
+pub fn command_name(path: &str) -> darling::Result<()> {
+    let segments = path.split(' ').collect::<Vec<_>>();
+    if segments.len() > MAX_COMMAND_DEPTH {
+        return Err(darling::Error::custom(format!(
+            "Command `{}` is nested too deeply; at most {} segments are allowed",
+            path, MAX_COMMAND_DEPTH
+        )));
+    }
+    segments.into_iter().try_for_each(name)
+}
+
+/// Checks that a command or option description is between 1 and 100 characters long
+pub fn description(description: &str) -> darling::Result<()> {
+    let length = description.chars().count();
+    if length == 0 || length > MAX_DESCRIPTION_LENGTH {
+        return Err(darling::Error::custom(format!(
+            "Description must be between 1 and {} characters long (found {})",
+            MAX_DESCRIPTION_LENGTH, length
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that a choice name is between 1 and 100 characters long
+pub fn choice_name(name: &str) -> darling::Result<()> {
+    let length = name.chars().count();
+    if length == 0 || length > MAX_CHOICE_LENGTH {
+        return Err(darling::Error::custom(format!(
+            "Choice name `{}` must be between 1 and {} characters long",
+            name, MAX_CHOICE_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that a string choice value is at most 100 characters long
+pub fn choice_value(value: &str) -> darling::Result<()> {
+    if value.chars().count() > MAX_CHOICE_LENGTH {
+        return Err(darling::Error::custom(format!(
+            "Choice value `{}` must be at most {} characters long",
+            value, MAX_CHOICE_LENGTH
+        )));
+    }
+    Ok(())
+}